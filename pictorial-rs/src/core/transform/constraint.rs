@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A rule applied to every drag update. Some types read extra settings from `params`:
+///
+/// - `snap-rotation`: `increment` in degrees (default 15)
+/// - `maintain-aspect`: `ratio` as width / height, or `width` and `height` (e.g. 16 and 9);
+///   without them the starting proportions are kept
+/// - `scale-limits`: `min` and/or `max` scale factor for one drag
+/// - `lock-axis`: `axis` of `"x"` or `"y"`; without it the axis the pointer moved
+///   further along wins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Constraint {
+    pub constraint_type: ConstraintType,
+    pub enabled: bool,
+    pub params: Option<HashMap<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConstraintType {
+    SnapToGrid,
+    SnapToObject,
+    SnapToGuides,
+    MaintainAspect,
+    LockRotation,
+    LockScale,
+    SnapRotation,
+    ScaleLimits,
+    LockAxis,
+}
+
+impl Constraint {
+    pub fn new(constraint_type: ConstraintType, enabled: bool) -> Constraint {
+        Constraint {
+            constraint_type,
+            enabled,
+            params: None,
+        }
+    }
+
+    pub fn with_param(mut self, key: &str, value: impl Into<serde_json::Value>) -> Constraint {
+        self.params
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn param_f64(&self, key: &str) -> Option<f64> {
+        self.params.as_ref()?.get(key)?.as_f64()
+    }
+
+    pub fn param_str(&self, key: &str) -> Option<&str> {
+        self.params.as_ref()?.get(key)?.as_str()
+    }
+}
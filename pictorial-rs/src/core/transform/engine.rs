@@ -1,59 +1,213 @@
-use crate::core::vector::{VectorElement, Point, BoundingBox, Transform, GridSettings, Selection, Viewport};
+use super::{Constraint, ConstraintType, FrameGrids, HandleType, SelectionHandle};
+use crate::core::vector::{
+    AlignmentType, Document, VectorElement, Point, BoundingBox, Transform, GridSettings, RulerGuide, Selection, Viewport,
+};
+use crate::math::Matrix3;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::str::FromStr;
+use thiserror::Error;
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TransformError {
+    #[error("a transform is already in progress")]
+    AlreadyTransforming,
+    #[error("unknown element: {0}")]
+    UnknownElement(String),
+    #[error("invalid parameters: {0}")]
+    InvalidParameters(String),
+    #[error("unknown guide: {0}")]
+    UnknownGuide(String),
+    #[error("guide is locked: {0}")]
+    GuideLocked(String),
+}
+
+impl From<TransformError> for JsValue {
+    fn from(error: TransformError) -> JsValue {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct SelectionHandle {
+pub struct AlignmentGuide {
     pub id: String,
-    pub handle_type: HandleType,
-    pub position: Point,
-    pub cursor: String,
-    pub bounds: BoundingBox,
+    pub guide_type: AlignmentType,
+    pub position: f64,
+    pub elements: Vec<String>,
+    pub temporary: bool,
 }
 
-#[wasm_bindgen]
+/// Gaps between neighbours that ended up equal, drawn as measurement markers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpacingGuide {
+    pub id: String,
+    pub axis: SpacingAxis,
+    pub gap: f64,
+    pub markers: Vec<GapMarker>,
+    pub elements: Vec<String>,
+    pub temporary: bool,
+}
+
+/// `Horizontal` spacing measures gaps along x between elements in the same row,
+/// `Vertical` along y between elements in the same column.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum HandleType {
-    Corner,
-    Edge,
-    Rotation,
+#[serde(rename_all = "lowercase")]
+pub enum SpacingAxis {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    Left,
     Center,
+    Right,
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl FromStr for Alignment {
+    type Err = TransformError;
+
+    fn from_str(value: &str) -> Result<Alignment, TransformError> {
+        match value {
+            "left" => Ok(Alignment::Left),
+            "center" => Ok(Alignment::Center),
+            "right" => Ok(Alignment::Right),
+            "top" => Ok(Alignment::Top),
+            "middle" => Ok(Alignment::Middle),
+            "bottom" => Ok(Alignment::Bottom),
+            _ => Err(TransformError::InvalidParameters(format!("unknown alignment: {}", value))),
+        }
+    }
 }
 
+/// What `align_elements` lines the elements up against.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Constraint {
-    pub constraint_type: ConstraintType,
-    pub enabled: bool,
-    pub params: Option<HashMap<String, serde_json::Value>>,
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum AlignTarget {
+    /// The bounds of all the elements being aligned.
+    Selection,
+    /// One of the elements, which stays where it is ("align to key object").
+    KeyElement { id: String },
+    /// The frame or artboard containing the elements.
+    Frame { bounds: BoundingBox },
 }
 
+/// How `distribute_elements` spaces the selection along a `SpacingAxis`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum Distribution {
+    /// Evenly spaced centers between the first and last element.
+    Centers,
+    /// Equal gaps between the first and last element.
+    Gaps,
+    /// A fixed gap, starting from the first element.
+    FixedGap { gap: f64 },
+}
+
+/// The point of the bounds that the numeric X/Y fields refer to, and that stays
+/// fixed when width, height or rotation are edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
-pub enum ConstraintType {
-    SnapToGrid,
-    SnapToObject,
-    MaintainAspect,
-    LockRotation,
-    LockScale,
+pub enum ReferencePoint {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    MiddleLeft,
+    Center,
+    MiddleRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl ReferencePoint {
+    pub fn position(&self, bounds: &BoundingBox) -> Point {
+        let (fx, fy) = match self {
+            ReferencePoint::TopLeft => (0.0, 0.0),
+            ReferencePoint::TopCenter => (0.5, 0.0),
+            ReferencePoint::TopRight => (1.0, 0.0),
+            ReferencePoint::MiddleLeft => (0.0, 0.5),
+            ReferencePoint::Center => (0.5, 0.5),
+            ReferencePoint::MiddleRight => (1.0, 0.5),
+            ReferencePoint::BottomLeft => (0.0, 1.0),
+            ReferencePoint::BottomCenter => (0.5, 1.0),
+            ReferencePoint::BottomRight => (1.0, 1.0),
+        };
+        Point::new(bounds.x + bounds.width * fx, bounds.y + bounds.height * fy)
+    }
+}
+
+/// Values shown in the transform panel. Position and size are the canvas-aligned
+/// bounds; rotation is in radians. Fields left as `None` are not changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformValues {
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub rotation: Option<f64>,
+}
+
+/// Whether panel edits on a multi-selection apply to the selection as one box
+/// or to every element on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransformScope {
+    Selection,
+    Each,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NudgeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// What an arrow key changes. Modifier keys pick `Rotate` or `Scale`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NudgeMode {
+    #[default]
+    Move,
+    Rotate,
+    Scale,
+}
+
+/// Which pixels pixel snapping lines bounds up with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PixelGrid {
+    /// Pixels of the exported image, `1 / export_scale` canvas units apart.
+    #[default]
+    Export,
+    /// Device pixels on screen at the current zoom.
+    Screen,
 }
 
+/// The nudged elements plus the history entry for the whole run of nudges so far.
+/// When `merged` is set, `action` replaces the previous entry instead of adding one.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct AlignmentGuide {
-    pub id: String,
-    pub guide_type: AlignmentType,
-    pub position: f64,
-    pub elements: Vec<String>,
-    pub temporary: bool,
+pub struct NudgeResult {
+    pub elements: Vec<VectorElement>,
+    pub action: TransformAction,
+    pub merged: bool,
 }
 
+/// One gap, running from `start` to `end` along the spacing axis at `cross` on the other axis.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum AlignmentType {
-    Horizontal,
-    Vertical,
+pub struct GapMarker {
+    pub start: f64,
+    pub end: f64,
+    pub cross: f64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -62,6 +216,7 @@ pub struct SnapResult {
     pub position: Point,
     pub offset: Point,
     pub guides: Vec<AlignmentGuide>,
+    pub spacing_guides: Vec<SpacingGuide>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -82,14 +237,62 @@ pub struct TransformAction {
     pub constraints_met: Vec<String>,
 }
 
+/// The transformed elements and the guides to draw for one pointer update.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformUpdate {
+    pub elements: Vec<VectorElement>,
+    pub guides: Vec<AlignmentGuide>,
+    pub spacing_guides: Vec<SpacingGuide>,
+}
+
+/// A box as `(min, max, cross_min, cross_max)` along one axis.
+type Extent = (f64, f64, f64, f64);
+
+/// Consecutive nudges of the same elements in the same mode, folded into one action.
+/// Like a drag session, every nudge applies the whole `delta` to the elements as they
+/// were before the run, so bounds don't grow with each rotation step.
+#[derive(Debug, Clone)]
+struct NudgeRun {
+    mode: NudgeMode,
+    element_ids: Vec<String>,
+    origin: Point,
+    delta: Transform,
+    initial_elements: Vec<VectorElement>,
+    elements: Vec<VectorElement>,
+}
+
+/// State captured when a drag starts. Every update is computed from the initial
+/// elements, so errors never accumulate and cancelling restores them exactly.
+#[derive(Debug, Clone)]
+struct TransformSession {
+    action_type: TransformActionType,
+    handle_type: HandleType,
+    start_point: Point,
+    origin: Point,
+    initial_elements: Vec<VectorElement>,
+    snap_targets: Vec<VectorElement>,
+    /// The document's ruler guides that apply to the dragged elements.
+    ruler_guides: Vec<RulerGuide>,
+    delta: Transform,
+}
+
 #[wasm_bindgen]
 pub struct TransformEngine {
     grid: GridSettings,
+    frame_grids: Vec<FrameGrids>,
     constraints: Vec<Constraint>,
     alignment_guides: Vec<AlignmentGuide>,
     snap_threshold: f64,
+    pixel_snapping: bool,
+    pixel_grid: PixelGrid,
+    export_scale: f64,
     viewport: Viewport,
-    current_action: Option<TransformAction>,
+    session: Option<TransformSession>,
+    nudge_run: Option<NudgeRun>,
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(value).map_err(JsValue::from)
 }
 
 #[wasm_bindgen]
@@ -104,388 +307,238 @@ impl TransformEngine {
                 opacity: 0.5,
                 snap: false,
             },
+            frame_grids: Vec::new(),
             constraints: vec![
-                Constraint {
-                    constraint_type: ConstraintType::SnapToGrid,
-                    enabled: false,
-                    params: None,
-                },
-                Constraint {
-                    constraint_type: ConstraintType::SnapToObject,
-                    enabled: true,
-                    params: None,
-                },
-                Constraint {
-                    constraint_type: ConstraintType::MaintainAspect,
-                    enabled: false,
-                    params: None,
-                },
-                Constraint {
-                    constraint_type: ConstraintType::LockRotation,
-                    enabled: false,
-                    params: None,
-                },
-                Constraint {
-                    constraint_type: ConstraintType::LockScale,
-                    enabled: false,
-                    params: None,
-                },
+                Constraint::new(ConstraintType::SnapToGrid, false),
+                Constraint::new(ConstraintType::SnapToObject, true),
+                Constraint::new(ConstraintType::SnapToGuides, true),
+                Constraint::new(ConstraintType::MaintainAspect, false),
+                Constraint::new(ConstraintType::LockRotation, false),
+                Constraint::new(ConstraintType::LockScale, false),
+                Constraint::new(ConstraintType::SnapRotation, false),
+                Constraint::new(ConstraintType::ScaleLimits, false),
+                Constraint::new(ConstraintType::LockAxis, false),
             ],
             alignment_guides: Vec::new(),
             snap_threshold: 5.0,
+            pixel_snapping: false,
+            pixel_grid: PixelGrid::Export,
+            export_scale: 1.0,
             viewport: Viewport::new(0.0, 0.0, 1.0, 800.0, 600.0),
-            current_action: None,
+            session: None,
+            nudge_run: None,
         }
     }
 
-    // SELECTION HANDLES
-    pub fn generate_selection_handles(&self, selection: &Selection) -> Vec<SelectionHandle> {
-        let mut handles = Vec::new();
-        let bounds = &selection.bounds;
-        let handle_size = 8.0 / self.viewport.zoom;
+    #[wasm_bindgen(js_name = generate_selection_handles)]
+    pub fn generate_selection_handles_js(&self, selection: JsValue) -> Result<JsValue, JsValue> {
+        let selection: Selection = serde_wasm_bindgen::from_value(selection)?;
+        to_js(&self.generate_selection_handles(&selection))
+    }
 
-        // Corner handles
-        handles.extend(vec![
-            SelectionHandle {
-                id: "nw".to_string(),
-                handle_type: HandleType::Corner,
-                position: Point::new(bounds.x, bounds.y),
-                cursor: "nw-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x - handle_size / 2.0,
-                    bounds.y - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-            SelectionHandle {
-                id: "ne".to_string(),
-                handle_type: HandleType::Corner,
-                position: Point::new(bounds.x + bounds.width, bounds.y),
-                cursor: "ne-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x + bounds.width - handle_size / 2.0,
-                    bounds.y - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-            SelectionHandle {
-                id: "sw".to_string(),
-                handle_type: HandleType::Corner,
-                position: Point::new(bounds.x, bounds.y + bounds.height),
-                cursor: "sw-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x - handle_size / 2.0,
-                    bounds.y + bounds.height - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-            SelectionHandle {
-                id: "se".to_string(),
-                handle_type: HandleType::Corner,
-                position: Point::new(bounds.x + bounds.width, bounds.y + bounds.height),
-                cursor: "se-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x + bounds.width - handle_size / 2.0,
-                    bounds.y + bounds.height - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-        ]);
+    /// The selection box for `elements`, oriented for a single element; see
+    /// `Selection::from_elements`.
+    #[wasm_bindgen(js_name = selection_for_elements)]
+    pub fn selection_for_elements_js(elements: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&Selection::from_elements(&elements))
+    }
 
-        // Edge handles
-        handles.extend(vec![
-            SelectionHandle {
-                id: "n".to_string(),
-                handle_type: HandleType::Edge,
-                position: Point::new(bounds.x + bounds.width / 2.0, bounds.y),
-                cursor: "n-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x + bounds.width / 2.0 - handle_size / 2.0,
-                    bounds.y - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-            SelectionHandle {
-                id: "s".to_string(),
-                handle_type: HandleType::Edge,
-                position: Point::new(bounds.x + bounds.width / 2.0, bounds.y + bounds.height),
-                cursor: "s-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x + bounds.width / 2.0 - handle_size / 2.0,
-                    bounds.y + bounds.height - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-            SelectionHandle {
-                id: "w".to_string(),
-                handle_type: HandleType::Edge,
-                position: Point::new(bounds.x, bounds.y + bounds.height / 2.0),
-                cursor: "w-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x - handle_size / 2.0,
-                    bounds.y + bounds.height / 2.0 - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-            SelectionHandle {
-                id: "e".to_string(),
-                handle_type: HandleType::Edge,
-                position: Point::new(bounds.x + bounds.width, bounds.y + bounds.height / 2.0),
-                cursor: "e-resize".to_string(),
-                bounds: BoundingBox::new(
-                    bounds.x + bounds.width - handle_size / 2.0,
-                    bounds.y + bounds.height / 2.0 - handle_size / 2.0,
-                    handle_size,
-                    handle_size,
-                ),
-            },
-        ]);
+    #[wasm_bindgen(js_name = selection_handles)]
+    pub fn selection_handles_js(&self, elements: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&self.selection_handles(&elements))
+    }
 
-        // Rotation handle
-        let rotation_offset = 20.0 / self.viewport.zoom;
-        handles.push(SelectionHandle {
-            id: "rotation".to_string(),
-            handle_type: HandleType::Rotation,
-            position: Point::new(bounds.x + bounds.width / 2.0, bounds.y - rotation_offset),
-            cursor: "grab".to_string(),
-            bounds: BoundingBox::new(
-                bounds.x + bounds.width / 2.0 - handle_size / 2.0,
-                bounds.y - rotation_offset - handle_size / 2.0,
-                handle_size,
-                handle_size,
-            ),
-        });
+    #[wasm_bindgen(js_name = get_handle_at_point)]
+    pub fn get_handle_at_point_js(&self, handles: JsValue, point: &Point) -> Result<JsValue, JsValue> {
+        let handles: Vec<SelectionHandle> = serde_wasm_bindgen::from_value(handles)?;
+        to_js(&self.get_handle_at_point(&handles, point))
+    }
 
-        // Center handle
-        handles.push(SelectionHandle {
-            id: "center".to_string(),
-            handle_type: HandleType::Center,
-            position: Point::new(bounds.x + bounds.width / 2.0, bounds.y + bounds.height / 2.0),
-            cursor: "move".to_string(),
-            bounds: BoundingBox::new(
-                bounds.x + bounds.width / 2.0 - handle_size / 2.0,
-                bounds.y + bounds.height / 2.0 - handle_size / 2.0,
-                handle_size,
-                handle_size,
-            ),
-        });
+    #[wasm_bindgen(js_name = start_transform)]
+    pub fn start_transform_js(
+        &mut self,
+        element_ids: JsValue,
+        handle_type: HandleType,
+        start_point: Point,
+        document: JsValue,
+    ) -> Result<(), JsValue> {
+        let element_ids: Vec<String> = serde_wasm_bindgen::from_value(element_ids)?;
+        let document: Document = serde_wasm_bindgen::from_value(document)?;
+        self.start_transform(element_ids, handle_type, start_point, &document)?;
+        Ok(())
+    }
 
-        handles
+    #[wasm_bindgen(js_name = start_skew)]
+    pub fn start_skew_js(
+        &mut self,
+        element_ids: JsValue,
+        handle_type: HandleType,
+        start_point: Point,
+        document: JsValue,
+    ) -> Result<(), JsValue> {
+        let element_ids: Vec<String> = serde_wasm_bindgen::from_value(element_ids)?;
+        let document: Document = serde_wasm_bindgen::from_value(document)?;
+        self.start_skew(element_ids, handle_type, start_point, &document)?;
+        Ok(())
     }
 
-    pub fn get_handle_at_point(&self, handles: &[SelectionHandle], point: &Point) -> Option<SelectionHandle> {
-        for handle in handles {
-            if handle.bounds.contains_point(point) {
-                return Some(handle.clone());
-            }
-        }
-        None
+    #[wasm_bindgen(js_name = update_transform)]
+    pub fn update_transform_js(&mut self, current_point: Point) -> Result<JsValue, JsValue> {
+        to_js(&self.update_transform(current_point))
     }
 
-    // TRANSFORMATION
-    pub fn start_transform(&mut self, element_ids: Vec<String>, action_type: TransformActionType, origin: Point) {
-        self.current_action = Some(TransformAction {
-            action_type,
-            element_ids,
-            delta: Transform::identity(),
-            origin,
-            constraints_met: Vec::new(),
-        });
+    #[wasm_bindgen(js_name = finish_transform)]
+    pub fn finish_transform_js(&mut self) -> Result<JsValue, JsValue> {
+        to_js(&self.finish_transform())
     }
 
-    pub fn update_transform(&mut self, delta: Transform, elements: &[VectorElement]) -> (Vec<VectorElement>, Vec<AlignmentGuide>) {
-        if self.current_action.is_none() {
-            return (elements.to_vec(), Vec::new());
-        }
+    #[wasm_bindgen(js_name = cancel_transform)]
+    pub fn cancel_transform_js(&mut self) -> Result<JsValue, JsValue> {
+        to_js(&self.cancel_transform())
+    }
 
-        // Apply constraints
-        let constrained_delta = self.apply_constraints(&delta, elements);
-        
-        // Apply snapping
-        let snap_result = self.apply_snapping(&constrained_delta, elements);
-        
-        // Update current action
-        if let Some(ref mut action) = self.current_action {
-            action.delta = if snap_result.snapped {
-                self.add_transforms(&constrained_delta, &Transform {
-                    translate_x: snap_result.offset.x,
-                    translate_y: snap_result.offset.y,
-                    scale_x: 1.0,
-                    scale_y: 1.0,
-                    rotation: 0.0,
-                    skew_x: 0.0,
-                    skew_y: 0.0,
-                })
-            } else {
-                constrained_delta
-            };
-        }
+    /// `mode` defaults to moving when omitted.
+    #[wasm_bindgen(js_name = nudge)]
+    pub fn nudge_js(
+        &mut self,
+        element_ids: JsValue,
+        direction: JsValue,
+        big: bool,
+        elements: JsValue,
+        mode: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let element_ids: Vec<String> = serde_wasm_bindgen::from_value(element_ids)?;
+        let direction: NudgeDirection = serde_wasm_bindgen::from_value(direction)?;
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        let mode: Option<NudgeMode> = serde_wasm_bindgen::from_value(mode)?;
+        to_js(&self.nudge_with_mode(&element_ids, direction, big, mode.unwrap_or_default(), &elements)?)
+    }
 
-        // Transform elements
-        let transformed_elements: Vec<VectorElement> = elements
-            .iter()
-            .map(|element| {
-                if let Some(ref action) = self.current_action {
-                    self.transform_element(element, &action.delta, &action.origin)
-                } else {
-                    element.clone()
-                }
-            })
-            .collect();
+    /// Ends the current run of nudges, e.g. when the arrow key is released.
+    pub fn end_nudge(&mut self) {
+        self.nudge_run = None;
+    }
 
-        (transformed_elements, snap_result.guides)
+    pub fn is_transforming(&self) -> bool {
+        self.session.is_some()
     }
 
-    pub fn end_transform(&mut self) -> Option<TransformAction> {
-        let action = self.current_action.take();
-        self.clear_temporary_guides();
-        action
+    /// `target` defaults to the selection bounds when omitted.
+    #[wasm_bindgen(js_name = align_elements)]
+    pub fn align_elements_js(&self, elements: JsValue, alignment: &str, target: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        let alignment: Alignment = alignment.parse()?;
+        let target: AlignTarget = if target.is_undefined() || target.is_null() {
+            AlignTarget::Selection
+        } else {
+            serde_wasm_bindgen::from_value(target)?
+        };
+        to_js(&self.align_elements(&elements, alignment, &target)?)
     }
 
-    pub fn cancel_transform(&mut self) {
-        self.current_action = None;
-        self.clear_temporary_guides();
+    #[wasm_bindgen(js_name = transform_values)]
+    pub fn transform_values_js(elements: JsValue, reference: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        let reference: ReferencePoint = serde_wasm_bindgen::from_value(reference)?;
+        to_js(&Self::transform_values(&elements, reference))
     }
 
-    // ELEMENT TRANSFORMATION
-    pub fn transform_element(&self, element: &VectorElement, delta: &Transform, origin: &Point) -> VectorElement {
-        let mut new_element = element.clone();
-        
-        match new_element {
-            VectorElement::Path { ref mut transform, ref mut bounding_box, .. } => {
-                *transform = self.combine_transforms(transform, delta, origin);
-                *bounding_box = self.transform_bounds(&element.bounding_box(), transform);
-            }
-            VectorElement::Shape { ref mut transform, ref mut bounding_box, .. } => {
-                *transform = self.combine_transforms(transform, delta, origin);
-                *bounding_box = self.transform_bounds(&element.bounding_box(), transform);
-            }
-            VectorElement::Text { ref mut transform, ref mut bounding_box, .. } => {
-                *transform = self.combine_transforms(transform, delta, origin);
-                *bounding_box = self.transform_bounds(&element.bounding_box(), transform);
-            }
-            VectorElement::Group { ref mut transform, ref mut bounding_box, .. } => {
-                *transform = self.combine_transforms(transform, delta, origin);
-                *bounding_box = self.transform_bounds(&element.bounding_box(), transform);
-            }
-        }
-        
-        new_element
+    #[wasm_bindgen(js_name = set_transform_values)]
+    pub fn set_transform_values_js(
+        &self,
+        elements: JsValue,
+        values: JsValue,
+        reference: JsValue,
+        scope: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        let values: TransformValues = serde_wasm_bindgen::from_value(values)?;
+        let reference: ReferencePoint = serde_wasm_bindgen::from_value(reference)?;
+        let scope: TransformScope = serde_wasm_bindgen::from_value(scope)?;
+        to_js(&self.set_transform_values(&elements, &values, reference, scope)?)
     }
 
-    pub fn translate_element(&self, element: &VectorElement, offset: &Point) -> VectorElement {
-        let delta = Transform {
-            translate_x: offset.x,
-            translate_y: offset.y,
-            scale_x: 1.0,
-            scale_y: 1.0,
-            rotation: 0.0,
-            skew_x: 0.0,
-            skew_y: 0.0,
-        };
-        
-        self.transform_element(element, &delta, &Point::new(0.0, 0.0))
+    #[wasm_bindgen(js_name = distribute_elements)]
+    pub fn distribute_elements_js(&self, elements: JsValue, axis: JsValue, distribution: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        let axis: SpacingAxis = serde_wasm_bindgen::from_value(axis)?;
+        let distribution: Distribution = serde_wasm_bindgen::from_value(distribution)?;
+        to_js(&self.distribute_elements(&elements, &axis, &distribution))
     }
 
-    pub fn scale_element(&self, element: &VectorElement, scale: &Point, origin: &Point) -> VectorElement {
-        let delta = Transform {
-            translate_x: 0.0,
-            translate_y: 0.0,
-            scale_x: scale.x,
-            scale_y: scale.y,
-            rotation: 0.0,
-            skew_x: 0.0,
-            skew_y: 0.0,
-        };
-        
-        self.transform_element(element, &delta, origin)
+    #[wasm_bindgen(js_name = tidy_up)]
+    pub fn tidy_up_js(&self, elements: JsValue, gap: Option<f64>) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&self.tidy_up(&elements, gap))
     }
 
-    pub fn rotate_element(&self, element: &VectorElement, angle: f64, origin: &Point) -> VectorElement {
-        let delta = Transform {
-            translate_x: 0.0,
-            translate_y: 0.0,
-            scale_x: 1.0,
-            scale_y: 1.0,
-            rotation: angle,
-            skew_x: 0.0,
-            skew_y: 0.0,
-        };
-        
-        self.transform_element(element, &delta, origin)
+    #[wasm_bindgen(js_name = flip_horizontal)]
+    pub fn flip_horizontal_js(&self, elements: JsValue, keep_text_readable: bool) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&self.flip_horizontal(&elements, keep_text_readable))
     }
 
-    // ALIGNMENT
-    pub fn align_elements(&self, elements: &[VectorElement], alignment: &str) -> Vec<VectorElement> {
-        if elements.len() < 2 {
-            return elements.to_vec();
-        }
+    #[wasm_bindgen(js_name = flip_vertical)]
+    pub fn flip_vertical_js(&self, elements: JsValue, keep_text_readable: bool) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&self.flip_vertical(&elements, keep_text_readable))
+    }
 
-        let bounds: Vec<&BoundingBox> = elements.iter().map(|el| el.bounding_box()).collect();
-        
-        match alignment {
-            "left" => {
-                let align_to = bounds.iter().map(|b| b.x).fold(f64::INFINITY, f64::min);
-                elements.iter().map(|el| {
-                    let offset = Point::new(align_to - el.bounding_box().x, 0.0);
-                    self.translate_element(el, &offset)
-                }).collect()
-            }
-            "center" => {
-                let min_x = bounds.iter().map(|b| b.x).fold(f64::INFINITY, f64::min);
-                let max_x = bounds.iter().map(|b| b.x + b.width).fold(f64::NEG_INFINITY, f64::max);
-                let center_x = (min_x + max_x) / 2.0;
-                
-                elements.iter().map(|el| {
-                    let element_center = el.bounding_box().x + el.bounding_box().width / 2.0;
-                    let offset = Point::new(center_x - element_center, 0.0);
-                    self.translate_element(el, &offset)
-                }).collect()
-            }
-            "right" => {
-                let align_to = bounds.iter().map(|b| b.x + b.width).fold(f64::NEG_INFINITY, f64::max);
-                elements.iter().map(|el| {
-                    let offset = Point::new(align_to - (el.bounding_box().x + el.bounding_box().width), 0.0);
-                    self.translate_element(el, &offset)
-                }).collect()
-            }
-            "top" => {
-                let align_to = bounds.iter().map(|b| b.y).fold(f64::INFINITY, f64::min);
-                elements.iter().map(|el| {
-                    let offset = Point::new(0.0, align_to - el.bounding_box().y);
-                    self.translate_element(el, &offset)
-                }).collect()
-            }
-            "middle" => {
-                let min_y = bounds.iter().map(|b| b.y).fold(f64::INFINITY, f64::min);
-                let max_y = bounds.iter().map(|b| b.y + b.height).fold(f64::NEG_INFINITY, f64::max);
-                let center_y = (min_y + max_y) / 2.0;
-                
-                elements.iter().map(|el| {
-                    let element_center = el.bounding_box().y + el.bounding_box().height / 2.0;
-                    let offset = Point::new(0.0, center_y - element_center);
-                    self.translate_element(el, &offset)
-                }).collect()
-            }
-            "bottom" => {
-                let align_to = bounds.iter().map(|b| b.y + b.height).fold(f64::NEG_INFINITY, f64::max);
-                elements.iter().map(|el| {
-                    let offset = Point::new(0.0, align_to - (el.bounding_box().y + el.bounding_box().height));
-                    self.translate_element(el, &offset)
-                }).collect()
-            }
-            _ => elements.to_vec(),
-        }
+    #[wasm_bindgen(js_name = reflect_across)]
+    pub fn reflect_across_js(
+        &self,
+        elements: JsValue,
+        axis_point: &Point,
+        angle: f64,
+        keep_text_readable: bool,
+    ) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&self.reflect_across(&elements, axis_point, angle, keep_text_readable))
+    }
+
+    /// `pivot` may be null to reset the selection to its default pivots.
+    #[wasm_bindgen(js_name = set_pivot)]
+    pub fn set_pivot_js(&self, elements: JsValue, pivot: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        let pivot: Option<Point> = serde_wasm_bindgen::from_value(pivot)?;
+        to_js(&self.set_pivot(&elements, pivot.as_ref()))
+    }
+
+    #[wasm_bindgen(js_name = snap_pivot)]
+    pub fn snap_pivot_js(&self, elements: JsValue, point: &Point) -> Result<Point, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        Ok(self.snap_pivot(&elements, point))
+    }
+
+    #[wasm_bindgen(js_name = selection_pivot)]
+    pub fn selection_pivot_js(elements: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&Self::selection_pivot(&elements))
+    }
+
+    /// `params` may be omitted; see `Constraint` for what each type reads.
+    #[wasm_bindgen(js_name = set_constraint)]
+    pub fn set_constraint_js(&mut self, constraint_type: JsValue, enabled: bool, params: JsValue) -> Result<(), JsValue> {
+        let constraint_type: ConstraintType = serde_wasm_bindgen::from_value(constraint_type)?;
+        let params = serde_wasm_bindgen::from_value(params)?;
+        self.update_constraint(Constraint {
+            constraint_type,
+            enabled,
+            params,
+        });
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = constraints)]
+    pub fn constraints_js(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.constraints)
     }
 
     // GRID AND SNAPPING
+
+    /// `snap` switches the `SnapToGrid` constraint, which is what grid snapping reads.
     pub fn set_grid(&mut self, enabled: bool, size: f64, color: String, opacity: f64, snap: bool) {
         self.grid = GridSettings {
             enabled,
@@ -494,149 +547,2601 @@ impl TransformEngine {
             opacity,
             snap,
         };
+        self.set_constraint(ConstraintType::SnapToGrid, snap);
     }
 
+    /// Snaps to the layout grids of the innermost frame containing `point`, or else to
+    /// the canvas grid when it is shown. Both follow the `SnapToGrid` constraint.
     pub fn snap_to_grid(&self, point: &Point) -> Point {
-        if !self.grid.enabled || !self.grid.snap {
-            return *point;
-        }
-        
-        Point::new(
-            (point.x / self.grid.size).round() * self.grid.size,
-            (point.y / self.grid.size).round() * self.grid.size,
-        )
+        let (x, y) = self.grid_snap_axes(point);
+        Point::new(x.unwrap_or(point.x), y.unwrap_or(point.y))
     }
 
-    // VIEWPORT
-    pub fn set_viewport(&mut self, viewport: Viewport) {
-        self.viewport = viewport;
+    #[wasm_bindgen(js_name = set_frame_grids)]
+    pub fn set_frame_grids_js(&mut self, frame: JsValue) -> Result<(), JsValue> {
+        let frame: FrameGrids = serde_wasm_bindgen::from_value(frame)?;
+        self.set_frame_grids(frame);
+        Ok(())
     }
 
-    pub fn get_viewport(&self) -> &Viewport {
-        &self.viewport
+    pub fn remove_frame_grids(&mut self, frame_id: &str) {
+        self.frame_grids.retain(|frame| frame.frame_id != frame_id);
     }
 
-    // PRIVATE HELPER METHODS
-    fn apply_snapping(&self, delta: &Transform, elements: &[VectorElement]) -> SnapResult {
-        let guides = Vec::new();
-        let snapped_x = false;
-        let snapped_y = false;
-        let offset_x = 0.0;
-        let offset_y = 0.0;
-
-        if !self.is_constraint_enabled(&ConstraintType::SnapToObject) {
-            return SnapResult {
-                snapped: false,
-                position: Point::new(delta.translate_x, delta.translate_y),
-                offset: Point::new(0.0, 0.0),
-                guides,
-            };
-        }
+    pub fn set_snap_threshold(&mut self, threshold: f64) {
+        self.snap_threshold = threshold;
+    }
 
-        // Simplified snapping logic - would need full implementation
-        SnapResult {
-            snapped: snapped_x || snapped_y,
-            position: Point::new(delta.translate_x + offset_x, delta.translate_y + offset_y),
-            offset: Point::new(offset_x, offset_y),
-            guides,
-        }
+    #[wasm_bindgen(js_name = alignment_guides)]
+    pub fn alignment_guides_js(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.alignment_guides)
     }
 
-    fn is_constraint_enabled(&self, constraint_type: &ConstraintType) -> bool {
-        self.constraints
-            .iter()
-            .find(|c| &c.constraint_type == constraint_type)
-            .map(|c| c.enabled)
-            .unwrap_or(false)
+    /// Returns the document with the new guide appended. `artboard_id` may be omitted for
+    /// a guide that applies everywhere.
+    #[wasm_bindgen(js_name = add_ruler_guide)]
+    pub fn add_ruler_guide_js(
+        document: JsValue,
+        guide_type: JsValue,
+        position: f64,
+        artboard_id: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let mut document: Document = serde_wasm_bindgen::from_value(document)?;
+        let guide_type: AlignmentType = serde_wasm_bindgen::from_value(guide_type)?;
+        Self::add_ruler_guide(&mut document, guide_type, position, artboard_id);
+        to_js(&document)
     }
 
-    fn apply_constraints(&self, delta: &Transform, _elements: &[VectorElement]) -> Transform {
-        let mut constrained_delta = *delta;
+    #[wasm_bindgen(js_name = move_ruler_guide)]
+    pub fn move_ruler_guide_js(document: JsValue, id: &str, position: f64) -> Result<JsValue, JsValue> {
+        let mut document: Document = serde_wasm_bindgen::from_value(document)?;
+        Self::move_ruler_guide(&mut document, id, position)?;
+        to_js(&document)
+    }
 
-        // Maintain aspect ratio
-        if self.is_constraint_enabled(&ConstraintType::MaintainAspect) 
-            && (delta.scale_x != 1.0 || delta.scale_y != 1.0) {
-            if delta.scale_x.abs() > delta.scale_y.abs() {
-                constrained_delta.scale_y = delta.scale_x;
-            } else {
-                constrained_delta.scale_x = delta.scale_y;
-            }
-        }
+    #[wasm_bindgen(js_name = delete_ruler_guide)]
+    pub fn delete_ruler_guide_js(document: JsValue, id: &str) -> Result<JsValue, JsValue> {
+        let mut document: Document = serde_wasm_bindgen::from_value(document)?;
+        Self::delete_ruler_guide(&mut document, id)?;
+        to_js(&document)
+    }
 
-        // Lock rotation
-        if self.is_constraint_enabled(&ConstraintType::LockRotation) {
-            constrained_delta.rotation = 0.0;
-        }
+    #[wasm_bindgen(js_name = set_ruler_guide_locked)]
+    pub fn set_ruler_guide_locked_js(document: JsValue, id: &str, locked: bool) -> Result<JsValue, JsValue> {
+        let mut document: Document = serde_wasm_bindgen::from_value(document)?;
+        Self::set_ruler_guide_locked(&mut document, id, locked)?;
+        to_js(&document)
+    }
 
-        // Lock scale
-        if self.is_constraint_enabled(&ConstraintType::LockScale) {
-            constrained_delta.scale_x = 1.0;
-            constrained_delta.scale_y = 1.0;
+    /// While on, drags leave element bounds on whole pixels of the pixel grid, which by
+    /// default is the export at `export_scale`.
+    pub fn set_pixel_snapping(&mut self, enabled: bool, export_scale: f64) {
+        self.pixel_snapping = enabled;
+        if export_scale > 0.0 {
+            self.export_scale = export_scale;
         }
+    }
 
-        // Snap to grid
-        if self.is_constraint_enabled(&ConstraintType::SnapToGrid) {
-            let snapped_translation = self.snap_to_grid(&Point::new(delta.translate_x, delta.translate_y));
-            constrained_delta.translate_x = snapped_translation.x;
-            constrained_delta.translate_y = snapped_translation.y;
-        }
+    #[wasm_bindgen(js_name = set_pixel_grid)]
+    pub fn set_pixel_grid_js(&mut self, pixel_grid: JsValue) -> Result<(), JsValue> {
+        self.set_pixel_grid(serde_wasm_bindgen::from_value(pixel_grid)?);
+        Ok(())
+    }
 
-        constrained_delta
+    #[wasm_bindgen(js_name = snap_to_pixels)]
+    pub fn snap_to_pixels_js(&self, elements: JsValue) -> Result<JsValue, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        to_js(&self.snap_to_pixels(&elements))
     }
 
-    fn add_transforms(&self, a: &Transform, b: &Transform) -> Transform {
-        Transform {
-            translate_x: a.translate_x + b.translate_x,
-            translate_y: a.translate_y + b.translate_y,
-            scale_x: a.scale_x * b.scale_x,
-            scale_y: a.scale_y * b.scale_y,
-            rotation: a.rotation + b.rotation,
-            skew_x: a.skew_x + b.skew_x,
-            skew_y: a.skew_y + b.skew_y,
-        }
+    // VIEWPORT
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
     }
 
-    fn combine_transforms(&self, base: &Transform, delta: &Transform, origin: &Point) -> Transform {
-        let cos_r = delta.rotation.cos();
-        let sin_r = delta.rotation.sin();
-        
-        let dx = origin.x;
-        let dy = origin.y;
-        
-        Transform {
-            translate_x: base.translate_x + delta.translate_x + dx * (delta.scale_x * cos_r - 1.0) + dy * (delta.scale_x * sin_r),
-            translate_y: base.translate_y + delta.translate_y + dx * (-delta.scale_y * sin_r) + dy * (delta.scale_y * cos_r - 1.0),
-            scale_x: base.scale_x * delta.scale_x,
-            scale_y: base.scale_y * delta.scale_y,
-            rotation: base.rotation + delta.rotation,
-            skew_x: base.skew_x + delta.skew_x,
-            skew_y: base.skew_y + delta.skew_y,
-        }
+    pub fn get_viewport(&self) -> Viewport {
+        self.viewport.clone()
     }
+}
 
-    fn transform_bounds(&self, bounds: &BoundingBox, transform: &Transform) -> BoundingBox {
-        // Transform the four corners of the bounding box
-        let corners = [
-            Point::new(bounds.x, bounds.y),
-            Point::new(bounds.x + bounds.width, bounds.y),
-            Point::new(bounds.x + bounds.width, bounds.y + bounds.height),
-            Point::new(bounds.x, bounds.y + bounds.height),
-        ];
+impl TransformEngine {
+    // SELECTION HANDLES
+
+    /// Handles for the selection's oriented box: `selection.bounds` is the box in the
+    /// selection's own frame and `selection.transform` places it on the canvas, so a
+    /// rotated element gets handles on its rotated corners with matching cursors. Build
+    /// the selection with `Selection::from_elements`; an element's `bounding_box` is
+    /// already on the canvas and must not be paired with its transform.
+    pub fn generate_selection_handles(&self, selection: &Selection) -> Vec<SelectionHandle> {
+        let bounds = &selection.bounds;
+        let handle_size = 8.0 / self.viewport.zoom;
+        let rotation_offset = 20.0 / self.viewport.zoom;
+
+        let frame = selection.transform.to_matrix();
+        let rotation = frame.b.atan2(frame.a);
+        // The rotation handle sits above the box along the frame's own "up"
+        let (up_x, up_y) = frame.transform_vector(0.0, -1.0);
+        let up_length = up_x.hypot(up_y).max(f64::EPSILON);
 
-        let transformed_corners: Vec<Point> = corners
+        HandleType::ALL
             .iter()
-            .map(|corner| transform.transform_point(corner))
-            .collect();
-        
-        let min_x = transformed_corners.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
-        let min_y = transformed_corners.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
-        let max_x = transformed_corners.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
-        let max_y = transformed_corners.iter().map(|p| p.y).fold(f64::NEG_INFINITY, f64::max);
+            .map(|&handle_type| {
+                let local = handle_type.position(bounds);
+                let (mut x, mut y) = frame.transform_coords(local.x, local.y);
+                if handle_type == HandleType::Rotation {
+                    x += up_x / up_length * rotation_offset;
+                    y += up_y / up_length * rotation_offset;
+                }
 
-        BoundingBox::new(min_x, min_y, max_x - min_x, max_y - min_y)
+                let (dx, dy) = handle_type.direction();
+                let (dir_x, dir_y) = frame.transform_vector(dx, dy);
+
+                SelectionHandle {
+                    id: handle_type.id().to_string(),
+                    handle_type,
+                    position: Point::new(x, y),
+                    cursor: handle_type.cursor_at_angle(dir_y.atan2(dir_x)).to_string(),
+                    bounds: BoundingBox::new(
+                        x - handle_size / 2.0,
+                        y - handle_size / 2.0,
+                        handle_size,
+                        handle_size,
+                    ),
+                    rotation,
+                }
+            })
+            .collect()
     }
 
-    fn clear_temporary_guides(&mut self) {
-        self.alignment_guides.retain(|guide| !guide.temporary);
+    /// Handles for `elements`, from `Selection::from_elements`.
+    pub fn selection_handles(&self, elements: &[VectorElement]) -> Vec<SelectionHandle> {
+        Selection::from_elements(elements)
+            .map(|selection| self.generate_selection_handles(&selection))
+            .unwrap_or_default()
+    }
+
+    pub fn get_handle_at_point(&self, handles: &[SelectionHandle], point: &Point) -> Option<SelectionHandle> {
+        handles.iter().find(|handle| handle.contains_point(point)).cloned()
+    }
+
+    // TRANSFORMATION
+
+    /// Begins a drag on `handle_type`. The ids are looked up in `document`, whose ruler
+    /// guides are snapped to; scale handles keep the opposite corner or edge of the
+    /// selection fixed.
+    pub fn start_transform(
+        &mut self,
+        element_ids: Vec<String>,
+        handle_type: HandleType,
+        start_point: Point,
+        document: &Document,
+    ) -> Result<(), TransformError> {
+        let action_type = match handle_type {
+            HandleType::Center => TransformActionType::Translate,
+            HandleType::Rotation => TransformActionType::Rotate,
+            _ => TransformActionType::Scale,
+        };
+
+        self.begin_session(action_type, element_ids, handle_type, start_point, document)
+    }
+
+    /// Begins a skew drag (Ctrl-drag) on an edge handle. Top and bottom handles slant the
+    /// selection horizontally, left and right handles vertically; the opposite edge stays put.
+    pub fn start_skew(
+        &mut self,
+        element_ids: Vec<String>,
+        handle_type: HandleType,
+        start_point: Point,
+        document: &Document,
+    ) -> Result<(), TransformError> {
+        if !handle_type.is_edge() {
+            return Err(TransformError::InvalidParameters(format!(
+                "skew needs an edge handle, got {}",
+                handle_type.id()
+            )));
+        }
+
+        self.begin_session(TransformActionType::Skew, element_ids, handle_type, start_point, document)
     }
-} 
\ No newline at end of file
+
+    pub fn update_transform(&mut self, current_point: Point) -> Option<TransformUpdate> {
+        let session = self.session.as_ref()?;
+
+        // Apply constraints
+        let delta = self.pointer_delta(session, &current_point);
+        let constrained_delta = self.apply_constraints(&session.action_type, &delta, &session.initial_elements);
+
+        // Apply snapping
+        let snap_result = self.apply_snapping(
+            &session.action_type,
+            &constrained_delta,
+            &session.initial_elements,
+            &session.snap_targets,
+            &session.ruler_guides,
+        );
+        let delta = if snap_result.snapped {
+            self.add_transforms(&constrained_delta, &Transform::translate(snap_result.offset.x, snap_result.offset.y))
+        } else {
+            constrained_delta
+        };
+
+        // Keep moved and resized bounds on whole pixels. The correction goes into the delta,
+        // so the recorded action replays to exactly what was shown
+        let delta = if self.pixel_snapping { self.snap_delta_to_pixels(session, &delta) } else { delta };
+
+        // Transform elements
+        let elements = session
+            .initial_elements
+            .iter()
+            .map(|element| self.transform_element(element, &delta, &session.origin))
+            .collect();
+
+        if let Some(session) = self.session.as_mut() {
+            session.delta = delta;
+        }
+        self.clear_temporary_guides();
+        self.alignment_guides.extend(snap_result.guides.iter().cloned());
+
+        Some(TransformUpdate {
+            elements,
+            guides: snap_result.guides,
+            spacing_guides: snap_result.spacing_guides,
+        })
+    }
+
+    /// Ends the drag and returns the action to record in history.
+    pub fn finish_transform(&mut self) -> Option<TransformAction> {
+        let session = self.session.take()?;
+        self.clear_temporary_guides();
+
+        Some(TransformAction {
+            action_type: session.action_type,
+            element_ids: session.initial_elements.iter().map(|el| el.id().to_string()).collect(),
+            delta: session.delta,
+            origin: session.origin,
+            constraints_met: Vec::new(),
+        })
+    }
+
+    /// Cancels the drag and returns the elements as they were when it started,
+    /// so callers can revert them.
+    pub fn cancel_transform(&mut self) -> Option<Vec<VectorElement>> {
+        let session = self.session.take()?;
+        self.clear_temporary_guides();
+        Some(session.initial_elements)
+    }
+
+    pub fn transforming_elements(&self) -> Option<Vec<&str>> {
+        self.session
+            .as_ref()
+            .map(|s| s.initial_elements.iter().map(|el| el.id()).collect())
+    }
+
+    pub fn alignment_guides(&self) -> &[AlignmentGuide] {
+        &self.alignment_guides
+    }
+
+    // RULER GUIDES
+
+    /// Adds a guide to `document`. Drags started on the document snap to it.
+    pub fn add_ruler_guide(
+        document: &mut Document,
+        guide_type: AlignmentType,
+        position: f64,
+        artboard_id: Option<String>,
+    ) -> RulerGuide {
+        let guide = RulerGuide {
+            id: Uuid::new_v4().to_string(),
+            guide_type,
+            position,
+            locked: false,
+            artboard_id,
+        };
+        document.ruler_guides.push(guide.clone());
+        guide
+    }
+
+    pub fn move_ruler_guide(document: &mut Document, id: &str, position: f64) -> Result<(), TransformError> {
+        Self::unlocked_ruler_guide(document, id)?.position = position;
+        Ok(())
+    }
+
+    pub fn delete_ruler_guide(document: &mut Document, id: &str) -> Result<(), TransformError> {
+        Self::unlocked_ruler_guide(document, id)?;
+        document.ruler_guides.retain(|guide| guide.id != id);
+        Ok(())
+    }
+
+    pub fn set_ruler_guide_locked(document: &mut Document, id: &str, locked: bool) -> Result<(), TransformError> {
+        let guide = document
+            .ruler_guides
+            .iter_mut()
+            .find(|guide| guide.id == id)
+            .ok_or_else(|| TransformError::UnknownGuide(id.to_string()))?;
+        guide.locked = locked;
+        Ok(())
+    }
+
+    // NUDGE
+
+    /// Moves the elements by 1px (10px when `big`), or to the next grid line
+    /// (ten lines when `big`) while grid snapping is on.
+    pub fn nudge(
+        &mut self,
+        element_ids: &[String],
+        direction: NudgeDirection,
+        big: bool,
+        elements: &[VectorElement],
+    ) -> Result<NudgeResult, TransformError> {
+        self.nudge_with_mode(element_ids, direction, big, NudgeMode::Move, elements)
+    }
+
+    /// Like `nudge`, but arrow keys can also rotate by 1° (15° when `big`) or grow and
+    /// shrink the selection by 1px (10px) about its pivot. Right and up rotate clockwise
+    /// and grow. Nudges repeated on the same elements and mode merge into one action.
+    pub fn nudge_with_mode(
+        &mut self,
+        element_ids: &[String],
+        direction: NudgeDirection,
+        big: bool,
+        mode: NudgeMode,
+        elements: &[VectorElement],
+    ) -> Result<NudgeResult, TransformError> {
+        let selected = Self::find_elements(element_ids, elements)?;
+        let bounds = Self::selection_bounds(&selected)
+            .ok_or_else(|| TransformError::InvalidParameters("no elements to nudge".to_string()))?;
+
+        // The run carries on only while the elements are still the ones it produced
+        let (mut run, merged) = match self.nudge_run.take() {
+            Some(run) if run.mode == mode && run.element_ids == element_ids && run.elements == selected => (run, true),
+            _ => {
+                let origin = match mode {
+                    NudgeMode::Move => Point::new(0.0, 0.0),
+                    _ => Self::selection_pivot(&selected).unwrap_or_else(|| bounds.center()),
+                };
+                let run = NudgeRun {
+                    mode,
+                    element_ids: element_ids.to_vec(),
+                    origin,
+                    delta: Transform::identity(),
+                    initial_elements: selected.clone(),
+                    elements: selected,
+                };
+                (run, false)
+            }
+        };
+
+        let sign = match direction {
+            NudgeDirection::Right | NudgeDirection::Up => 1.0,
+            NudgeDirection::Left | NudgeDirection::Down => -1.0,
+        };
+        let delta = match mode {
+            NudgeMode::Move => {
+                let (dx, dy) = match direction {
+                    NudgeDirection::Left | NudgeDirection::Right => (self.nudge_step(bounds.x, sign, big), 0.0),
+                    NudgeDirection::Up | NudgeDirection::Down => (0.0, self.nudge_step(bounds.y, -sign, big)),
+                };
+                Transform::translate(dx, dy)
+            }
+            NudgeMode::Rotate => {
+                let degrees: f64 = if big { 15.0 } else { 1.0 };
+                Transform::rotate(sign * degrees.to_radians())
+            }
+            NudgeMode::Scale => {
+                // Grow the longer side by the step and keep the proportions
+                let size = bounds.width.max(bounds.height);
+                let step = if big { 10.0 } else { 1.0 };
+                if size <= 0.0 || size + sign * step <= 0.0 {
+                    Transform::identity()
+                } else {
+                    let factor = (size + sign * step) / size;
+                    Transform::scale(factor, factor)
+                }
+            }
+        };
+
+        run.delta = self.add_transforms(&run.delta, &delta);
+        let elements: Vec<VectorElement> = run
+            .initial_elements
+            .iter()
+            .map(|element| self.transform_element(element, &run.delta, &run.origin))
+            .collect();
+        run.elements = elements.clone();
+        let action = TransformAction {
+            action_type: match mode {
+                NudgeMode::Move => TransformActionType::Translate,
+                NudgeMode::Rotate => TransformActionType::Rotate,
+                NudgeMode::Scale => TransformActionType::Scale,
+            },
+            element_ids: element_ids.to_vec(),
+            delta: run.delta.clone(),
+            origin: run.origin,
+            constraints_met: Vec::new(),
+        };
+        self.nudge_run = Some(run);
+
+        Ok(NudgeResult {
+            elements,
+            action,
+            merged,
+        })
+    }
+
+    // ELEMENT TRANSFORMATION
+
+    /// Applies `delta` about `origin`: the element is moved so `origin` sits at zero,
+    /// transformed, and moved back.
+    pub fn transform_element(&self, element: &VectorElement, delta: &Transform, origin: &Point) -> VectorElement {
+        let matrix = Matrix3::translation(origin.x, origin.y)
+            * delta.to_matrix()
+            * Matrix3::translation(-origin.x, -origin.y);
+
+        self.apply_matrix(element, &matrix)
+    }
+
+    /// Applies a world-space matrix to the element's transform and bounding box.
+    pub fn apply_matrix(&self, element: &VectorElement, matrix: &Matrix3) -> VectorElement {
+        let mut new_element = element.clone();
+        let base = element.transform();
+
+        // Pure translations keep the stored components as-is instead of
+        // round-tripping them through a decomposition
+        *new_element.transform_mut() = if matrix.a == 1.0 && matrix.b == 0.0 && matrix.c == 0.0 && matrix.d == 1.0 {
+            Transform {
+                translate_x: base.translate_x + matrix.e,
+                translate_y: base.translate_y + matrix.f,
+                ..base.clone()
+            }
+        } else {
+            Transform::from_matrix(&(*matrix * base.to_matrix()))
+        };
+        *new_element.bounding_box_mut() = self.transform_bounds(element.bounding_box(), matrix);
+
+        new_element
+    }
+
+    pub fn translate_element(&self, element: &VectorElement, offset: &Point) -> VectorElement {
+        self.transform_element(element, &Transform::translate(offset.x, offset.y), &Point::new(0.0, 0.0))
+    }
+
+    pub fn scale_element(&self, element: &VectorElement, scale: &Point, origin: &Point) -> VectorElement {
+        self.transform_element(element, &Transform::scale(scale.x, scale.y), origin)
+    }
+
+    pub fn rotate_element(&self, element: &VectorElement, angle: f64, origin: &Point) -> VectorElement {
+        self.transform_element(element, &Transform::rotate(angle), origin)
+    }
+
+    /// Skews by `skew_x`/`skew_y` (radians) about `origin`; points on the line through
+    /// `origin` along the skew direction don't move.
+    pub fn skew_element(&self, element: &VectorElement, skew_x: f64, skew_y: f64, origin: &Point) -> VectorElement {
+        self.transform_element(element, &Transform::skew(skew_x, skew_y), origin)
+    }
+
+    // PIVOT
+
+    /// Stores `pivot` (canvas coordinates) on every element, in each element's local
+    /// space so it follows later moves. `None` goes back to the default pivots.
+    pub fn set_pivot(&self, elements: &[VectorElement], pivot: Option<&Point>) -> Vec<VectorElement> {
+        elements
+            .iter()
+            .map(|element| {
+                let mut new_element = element.clone();
+                *new_element.pivot_mut() = pivot.and_then(|point| {
+                    let local = element.transform().to_matrix().invert()?;
+                    let (x, y) = local.transform_coords(point.x, point.y);
+                    Some(Point::new(x, y))
+                });
+                new_element
+            })
+            .collect()
+    }
+
+    /// The custom pivot shared by the selection, in canvas coordinates. Elements whose
+    /// pivots were set separately (or not at all) have no shared pivot.
+    pub fn selection_pivot(elements: &[VectorElement]) -> Option<Point> {
+        let mut pivots = elements.iter().map(|element| {
+            let local = element.pivot()?;
+            Some(element.transform().transform_point(local))
+        });
+
+        let first = pivots.next()??;
+        for pivot in pivots {
+            let pivot = pivot?;
+            if (pivot.x - first.x).abs() > 1e-6 || (pivot.y - first.y).abs() > 1e-6 {
+                return None;
+            }
+        }
+        Some(first)
+    }
+
+    /// Pulls a dragged pivot onto the nearest of the selection's nine anchors
+    /// (corners, edge midpoints, center) when it is within the snap threshold. The
+    /// anchors sit on the same oriented box as the selection handles.
+    pub fn snap_pivot(&self, elements: &[VectorElement], point: &Point) -> Point {
+        let selection = match Selection::from_elements(elements) {
+            Some(selection) => selection,
+            None => return *point,
+        };
+        let frame = selection.transform.to_matrix();
+        let threshold = self.snap_threshold / self.viewport.zoom;
+
+        HandleType::ALL
+            .iter()
+            .filter(|handle_type| **handle_type != HandleType::Rotation)
+            .map(|handle_type| {
+                let local = handle_type.position(&selection.bounds);
+                let (x, y) = frame.transform_coords(local.x, local.y);
+                Point::new(x, y)
+            })
+            .map(|anchor| (anchor.distance_to(point), anchor))
+            .filter(|(distance, _)| *distance <= threshold)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, anchor)| anchor)
+            .unwrap_or(*point)
+    }
+
+    // REFLECTION
+
+    /// Mirrors the elements left-to-right about the selection's vertical center line.
+    pub fn flip_horizontal(&self, elements: &[VectorElement], keep_text_readable: bool) -> Vec<VectorElement> {
+        match Self::selection_bounds(elements) {
+            Some(bounds) => self.reflect_across(elements, &bounds.center(), std::f64::consts::FRAC_PI_2, keep_text_readable),
+            None => Vec::new(),
+        }
+    }
+
+    /// Mirrors the elements top-to-bottom about the selection's horizontal center line.
+    pub fn flip_vertical(&self, elements: &[VectorElement], keep_text_readable: bool) -> Vec<VectorElement> {
+        match Self::selection_bounds(elements) {
+            Some(bounds) => self.reflect_across(elements, &bounds.center(), 0.0, keep_text_readable),
+            None => Vec::new(),
+        }
+    }
+
+    /// Mirrors the elements across the line through `axis_point` at `angle` (radians).
+    /// With `keep_text_readable`, text elements move to their mirrored position and
+    /// turn to the mirrored angle of their baseline (`2 * angle - rotation`) without
+    /// their glyphs being mirrored. Of the two reading directions along that baseline,
+    /// the one closer to the text's current direction is kept, so flipping level text
+    /// leaves it level instead of upside down.
+    pub fn reflect_across(
+        &self,
+        elements: &[VectorElement],
+        axis_point: &Point,
+        angle: f64,
+        keep_text_readable: bool,
+    ) -> Vec<VectorElement> {
+        let matrix = Matrix3::translation(axis_point.x, axis_point.y)
+            * Matrix3::reflection(angle)
+            * Matrix3::translation(-axis_point.x, -axis_point.y);
+
+        elements
+            .iter()
+            .map(|element| match element {
+                VectorElement::Text { bounding_box, transform, .. } if keep_text_readable => {
+                    let center = bounding_box.center();
+                    let (x, y) = matrix.transform_coords(center.x, center.y);
+                    let turn = 2.0 * (angle - transform.rotation);
+                    let turn = turn - (turn / std::f64::consts::PI).round() * std::f64::consts::PI;
+                    let turned = self.rotate_element(element, turn, &center);
+                    self.translate_element(&turned, &Point::new(x - center.x, y - center.y))
+                }
+                _ => self.apply_matrix(element, &matrix),
+            })
+            .collect()
+    }
+
+    // NUMERIC TRANSFORM
+
+    /// Current panel values for the selection. Rotation is only reported when every
+    /// element shares it.
+    pub fn transform_values(elements: &[VectorElement], reference: ReferencePoint) -> Option<TransformValues> {
+        let bounds = Self::selection_bounds(elements)?;
+        let position = reference.position(&bounds);
+
+        Some(TransformValues {
+            x: Some(position.x),
+            y: Some(position.y),
+            width: Some(bounds.width),
+            height: Some(bounds.height),
+            rotation: Self::shared_rotation(elements),
+        })
+    }
+
+    /// Applies exact panel values: rotation first, then size, then position, each about
+    /// `reference`. With `MaintainAspect` on, editing one dimension scales the other
+    /// (width wins when both are given).
+    pub fn set_transform_values(
+        &self,
+        elements: &[VectorElement],
+        values: &TransformValues,
+        reference: ReferencePoint,
+        scope: TransformScope,
+    ) -> Result<Vec<VectorElement>, TransformError> {
+        let sizes = [values.width, values.height];
+        if sizes.iter().flatten().any(|size| !size.is_finite() || *size <= 0.0) {
+            return Err(TransformError::InvalidParameters("width and height must be positive".to_string()));
+        }
+
+        Ok(match scope {
+            TransformScope::Selection => self.apply_transform_values(elements, values, reference),
+            TransformScope::Each => elements
+                .iter()
+                .flat_map(|element| self.apply_transform_values(std::slice::from_ref(element), values, reference))
+                .collect(),
+        })
+    }
+
+    // ALIGNMENT
+    pub fn align_elements(&self, elements: &[VectorElement], alignment: Alignment, target: &AlignTarget) -> Result<Vec<VectorElement>, TransformError> {
+        let reference = match target {
+            AlignTarget::Selection => {
+                if elements.len() < 2 {
+                    return Ok(elements.to_vec());
+                }
+                Self::selection_bounds(elements)
+            }
+            AlignTarget::KeyElement { id } => elements
+                .iter()
+                .find(|el| el.id() == id)
+                .map(|el| el.bounding_box().clone()),
+            AlignTarget::Frame { bounds } => Some(bounds.clone()),
+        };
+
+        let reference = reference.ok_or_else(|| match target {
+            AlignTarget::KeyElement { id } => TransformError::UnknownElement(id.clone()),
+            _ => TransformError::InvalidParameters("nothing to align".to_string()),
+        })?;
+
+        Ok(elements
+            .iter()
+            .map(|el| {
+                // The key object stays put and everything else lines up with it
+                if matches!(target, AlignTarget::KeyElement { id } if id == el.id()) {
+                    return el.clone();
+                }
+
+                let b = el.bounding_box();
+                let offset = match alignment {
+                    Alignment::Left => Point::new(reference.x - b.x, 0.0),
+                    Alignment::Center => Point::new(reference.center().x - b.center().x, 0.0),
+                    Alignment::Right => Point::new((reference.x + reference.width) - (b.x + b.width), 0.0),
+                    Alignment::Top => Point::new(0.0, reference.y - b.y),
+                    Alignment::Middle => Point::new(0.0, reference.center().y - b.center().y),
+                    Alignment::Bottom => Point::new(0.0, (reference.y + reference.height) - (b.y + b.height)),
+                };
+                self.translate_element(el, &offset)
+            })
+            .collect())
+    }
+
+    // DISTRIBUTION
+
+    /// Spaces elements along `axis`. Results come back in input order so they can be
+    /// committed as one action.
+    pub fn distribute_elements(&self, elements: &[VectorElement], axis: &SpacingAxis, distribution: &Distribution) -> Vec<VectorElement> {
+        let min_count = match distribution {
+            Distribution::FixedGap { .. } => 2,
+            _ => 3,
+        };
+        if elements.len() < min_count {
+            return elements.to_vec();
+        }
+
+        let span = |el: &VectorElement| {
+            let b = el.bounding_box();
+            match axis {
+                SpacingAxis::Horizontal => (b.x, b.width),
+                SpacingAxis::Vertical => (b.y, b.height),
+            }
+        };
+
+        let mut order: Vec<usize> = (0..elements.len()).collect();
+        match distribution {
+            Distribution::Centers => order.sort_by(|&a, &b| {
+                let (sa, wa) = span(&elements[a]);
+                let (sb, wb) = span(&elements[b]);
+                (sa + wa / 2.0).total_cmp(&(sb + wb / 2.0))
+            }),
+            _ => order.sort_by(|&a, &b| span(&elements[a]).0.total_cmp(&span(&elements[b]).0)),
+        }
+
+        let (first_start, first_size) = span(&elements[order[0]]);
+        let (last_start, last_size) = span(&elements[order[order.len() - 1]]);
+        let steps = (order.len() - 1) as f64;
+
+        // Target start position along the axis for each element, in sorted order
+        let mut targets = Vec::with_capacity(order.len());
+        match distribution {
+            Distribution::Centers => {
+                let first_center = first_start + first_size / 2.0;
+                let step = (last_start + last_size / 2.0 - first_center) / steps;
+                for (i, &index) in order.iter().enumerate() {
+                    targets.push(first_center + step * i as f64 - span(&elements[index]).1 / 2.0);
+                }
+            }
+            Distribution::Gaps | Distribution::FixedGap { .. } => {
+                let gap = match distribution {
+                    Distribution::FixedGap { gap } => *gap,
+                    _ => {
+                        let total_size: f64 = order.iter().map(|&i| span(&elements[i]).1).sum();
+                        (last_start + last_size - first_start - total_size) / steps
+                    }
+                };
+                let mut position = first_start;
+                for &index in &order {
+                    targets.push(position);
+                    position += span(&elements[index]).1 + gap;
+                }
+            }
+        }
+
+        let mut result = elements.to_vec();
+        for (&index, target) in order.iter().zip(targets) {
+            let distance = target - span(&elements[index]).0;
+            let offset = match axis {
+                SpacingAxis::Horizontal => Point::new(distance, 0.0),
+                SpacingAxis::Vertical => Point::new(0.0, distance),
+            };
+            result[index] = self.translate_element(&elements[index], &offset);
+        }
+
+        result
+    }
+
+    /// Arranges elements into a grid, keeping their rough reading order. Rows are
+    /// detected from the current layout; `gap` defaults to the average existing gap.
+    pub fn tidy_up(&self, elements: &[VectorElement], gap: Option<f64>) -> Vec<VectorElement> {
+        let Some(selection) = Self::selection_bounds(elements) else {
+            return Vec::new();
+        };
+
+        // Group into rows: an element starts a new row once its center drops
+        // below the first element of the current row
+        let mut order: Vec<usize> = (0..elements.len()).collect();
+        order.sort_by(|&a, &b| elements[a].bounding_box().center().y.total_cmp(&elements[b].bounding_box().center().y));
+
+        let mut rows: Vec<Vec<usize>> = Vec::new();
+        for index in order {
+            let center_y = elements[index].bounding_box().center().y;
+            let starts_row = rows.last().is_none_or(|row| {
+                let first = elements[row[0]].bounding_box();
+                center_y > first.y + first.height
+            });
+
+            match rows.last_mut() {
+                Some(row) if !starts_row => row.push(index),
+                _ => rows.push(vec![index]),
+            }
+        }
+        for row in &mut rows {
+            row.sort_by(|&a, &b| elements[a].bounding_box().x.total_cmp(&elements[b].bounding_box().x));
+        }
+
+        let gap = gap.unwrap_or_else(|| {
+            let mut gaps = Vec::new();
+            for row in &rows {
+                for pair in row.windows(2) {
+                    let a = elements[pair[0]].bounding_box();
+                    gaps.push(elements[pair[1]].bounding_box().x - (a.x + a.width));
+                }
+            }
+            for pair in rows.windows(2) {
+                let bottom = pair[0].iter().map(|&i| {
+                    let b = elements[i].bounding_box();
+                    b.y + b.height
+                }).fold(f64::NEG_INFINITY, f64::max);
+                let top = pair[1].iter().map(|&i| elements[i].bounding_box().y).fold(f64::INFINITY, f64::min);
+                gaps.push(top - bottom);
+            }
+
+            if gaps.is_empty() {
+                0.0
+            } else {
+                (gaps.iter().sum::<f64>() / gaps.len() as f64).max(0.0)
+            }
+        });
+
+        // Each column is as wide as its widest element, each row as tall as its tallest
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut column_widths = vec![0.0_f64; columns];
+        for row in &rows {
+            for (column, &index) in row.iter().enumerate() {
+                column_widths[column] = column_widths[column].max(elements[index].bounding_box().width);
+            }
+        }
+
+        let mut result = elements.to_vec();
+        let mut y = selection.y;
+        for row in &rows {
+            let mut x = selection.x;
+            let mut row_height: f64 = 0.0;
+            for (column, &index) in row.iter().enumerate() {
+                let bounds = elements[index].bounding_box();
+                result[index] = self.translate_element(&elements[index], &Point::new(x - bounds.x, y - bounds.y));
+                x += column_widths[column] + gap;
+                row_height = row_height.max(bounds.height);
+            }
+            y += row_height + gap;
+        }
+
+        result
+    }
+
+    /// Sets the layout grids of a frame, replacing any it had.
+    pub fn set_frame_grids(&mut self, frame: FrameGrids) {
+        match self.frame_grids.iter_mut().find(|f| f.frame_id == frame.frame_id) {
+            Some(existing) => *existing = frame,
+            None => self.frame_grids.push(frame),
+        }
+    }
+
+    /// The smallest frame containing `point` that has an enabled grid.
+    pub fn frame_grids_at(&self, point: &Point) -> Option<&FrameGrids> {
+        self.frame_grids
+            .iter()
+            .filter(|frame| frame.bounds.contains_point(point) && frame.grids.iter().any(|grid| grid.enabled))
+            .min_by(|a, b| (a.bounds.width * a.bounds.height).total_cmp(&(b.bounds.width * b.bounds.height)))
+    }
+
+    pub fn set_pixel_grid(&mut self, pixel_grid: PixelGrid) {
+        self.pixel_grid = pixel_grid;
+    }
+
+    /// Size of one pixel of the pixel grid in canvas units.
+    pub fn pixel_size(&self) -> f64 {
+        match self.pixel_grid {
+            PixelGrid::Export => 1.0 / self.export_scale,
+            PixelGrid::Screen => 1.0 / self.viewport.zoom,
+        }
+    }
+
+    /// "Snap to pixel" cleanup: moves and resizes each element so its bounds sit on whole
+    /// pixels. Rotated and skewed elements are left alone, since resizing their
+    /// canvas-aligned bounds would distort them.
+    pub fn snap_to_pixels(&self, elements: &[VectorElement]) -> Vec<VectorElement> {
+        elements.iter().map(|element| self.snap_element_to_pixels(element)).collect()
+    }
+
+    // CONSTRAINTS
+    pub fn set_constraint(&mut self, constraint_type: ConstraintType, enabled: bool) {
+        match self.constraints.iter_mut().find(|c| c.constraint_type == constraint_type) {
+            Some(constraint) => constraint.enabled = enabled,
+            None => self.constraints.push(Constraint::new(constraint_type, enabled)),
+        }
+        if constraint_type == ConstraintType::SnapToGrid {
+            self.grid.snap = enabled;
+        }
+    }
+
+    /// Replaces the constraint of the same type, params included.
+    pub fn update_constraint(&mut self, constraint: Constraint) {
+        if constraint.constraint_type == ConstraintType::SnapToGrid {
+            self.grid.snap = constraint.enabled;
+        }
+        match self.constraints.iter_mut().find(|c| c.constraint_type == constraint.constraint_type) {
+            Some(existing) => *existing = constraint,
+            None => self.constraints.push(constraint),
+        }
+    }
+
+    pub fn is_constraint_enabled(&self, constraint_type: ConstraintType) -> bool {
+        self.enabled_constraint(constraint_type).is_some()
+    }
+
+    /// The constraint of this type, if it is switched on.
+    pub fn enabled_constraint(&self, constraint_type: ConstraintType) -> Option<&Constraint> {
+        self.constraints
+            .iter()
+            .find(|c| c.constraint_type == constraint_type && c.enabled)
+    }
+
+    pub fn selection_bounds(elements: &[VectorElement]) -> Option<BoundingBox> {
+        let mut bounds = elements.iter().map(|el| el.bounding_box().clone());
+        let first = bounds.next()?;
+        Some(bounds.fold(first, |acc, b| acc.union(&b)))
+    }
+
+    // PRIVATE HELPER METHODS
+
+    fn snap_element_to_pixels(&self, element: &VectorElement) -> VectorElement {
+        let pixel = self.pixel_size();
+        if !Self::is_axis_aligned(element.transform()) || !pixel.is_finite() {
+            return element.clone();
+        }
+
+        self.apply_matrix(element, &Self::pixel_fit(element.bounding_box(), pixel))
+    }
+
+    /// Corrects a drag's delta so the selection's bounds end on whole pixels. A move only
+    /// shifts the bounds and never changes their size; a resize of unrotated elements
+    /// rounds both edges so the size lands on whole pixels too. Other drags are unchanged.
+    fn snap_delta_to_pixels(&self, session: &TransformSession, delta: &Transform) -> Transform {
+        let pixel = self.pixel_size();
+        let bounds = match Self::selection_bounds(&session.initial_elements) {
+            Some(bounds) if pixel.is_finite() => bounds,
+            _ => return delta.clone(),
+        };
+        let snap = |value: f64| (value / pixel).round() * pixel;
+
+        match session.action_type {
+            TransformActionType::Translate => {
+                let (x, y) = (bounds.x + delta.translate_x, bounds.y + delta.translate_y);
+                Transform {
+                    translate_x: delta.translate_x + snap(x) - x,
+                    translate_y: delta.translate_y + snap(y) - y,
+                    ..delta.clone()
+                }
+            }
+            TransformActionType::Scale
+                if delta.rotation == 0.0
+                    && delta.skew_x == 0.0
+                    && delta.skew_y == 0.0
+                    && session.initial_elements.iter().all(|el| Self::is_axis_aligned(el.transform())) =>
+            {
+                let origin = &session.origin;
+                let about_origin = |matrix: Matrix3| {
+                    Matrix3::translation(origin.x, origin.y) * matrix * Matrix3::translation(-origin.x, -origin.y)
+                };
+                let scaled = self.transform_bounds(&bounds, &about_origin(delta.to_matrix()));
+                let fit = Self::pixel_fit(&scaled, pixel);
+                // The delta stays a scale about the same origin, so it only changes per axis
+                let corrected = Matrix3::translation(-origin.x, -origin.y)
+                    * fit
+                    * Matrix3::translation(origin.x, origin.y)
+                    * delta.to_matrix();
+                Transform {
+                    translate_x: corrected.e,
+                    translate_y: corrected.f,
+                    scale_x: corrected.a,
+                    scale_y: corrected.d,
+                    ..delta.clone()
+                }
+            }
+            _ => delta.clone(),
+        }
+    }
+
+    /// The move and scale that put both edges of `bounds` on whole pixels along each axis,
+    /// without collapsing a side that has length to zero.
+    fn pixel_fit(bounds: &BoundingBox, pixel: f64) -> Matrix3 {
+        let snap_span = |start: f64, length: f64| {
+            let snapped_start = (start / pixel).round() * pixel;
+            let mut snapped_end = ((start + length) / pixel).round() * pixel;
+            if length > 0.0 && snapped_end <= snapped_start {
+                snapped_end = snapped_start + pixel;
+            }
+            let scale = if length > 0.0 { (snapped_end - snapped_start) / length } else { 1.0 };
+            (snapped_start, scale)
+        };
+
+        let (x, scale_x) = snap_span(bounds.x, bounds.width);
+        let (y, scale_y) = snap_span(bounds.y, bounds.height);
+        Matrix3::translation(x, y) * Matrix3::scale(scale_x, scale_y) * Matrix3::translation(-bounds.x, -bounds.y)
+    }
+
+    /// Whether the transform only turns by whole quarter turns and doesn't skew, so the
+    /// element's canvas bounds hug its own box.
+    fn is_axis_aligned(transform: &Transform) -> bool {
+        let quarter_turns = transform.rotation / std::f64::consts::FRAC_PI_2;
+        (quarter_turns - quarter_turns.round()).abs() < 1e-9 && transform.skew_x == 0.0 && transform.skew_y == 0.0
+    }
+
+    fn unlocked_ruler_guide<'a>(document: &'a mut Document, id: &str) -> Result<&'a mut RulerGuide, TransformError> {
+        let guide = document
+            .ruler_guides
+            .iter_mut()
+            .find(|guide| guide.id == id)
+            .ok_or_else(|| TransformError::UnknownGuide(id.to_string()))?;
+        if guide.locked {
+            return Err(TransformError::GuideLocked(id.to_string()));
+        }
+        Ok(guide)
+    }
+
+    /// The ruler guides that apply to the moving elements: guides without an artboard,
+    /// plus those of an artboard among `shared_ancestors`, however deeply the elements
+    /// are nested inside it.
+    fn applicable_ruler_guides(guides: &[RulerGuide], shared_ancestors: &[String]) -> Vec<RulerGuide> {
+        guides
+            .iter()
+            .filter(|guide| match guide.artboard_id.as_deref() {
+                None => true,
+                Some(artboard) => shared_ancestors.iter().any(|id| id == artboard),
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Positions of the `ruler_guides` running along `guide_type`.
+    fn ruler_lines(&self, ruler_guides: &[RulerGuide], guide_type: &AlignmentType) -> Vec<f64> {
+        if !self.is_constraint_enabled(ConstraintType::SnapToGuides) {
+            return Vec::new();
+        }
+
+        ruler_guides
+            .iter()
+            .filter(|guide| guide.guide_type == *guide_type)
+            .map(|guide| guide.position)
+            .collect()
+    }
+
+    /// The grid position `point` snaps to on each axis, or `None` on an axis with
+    /// nothing to snap to.
+    fn grid_snap_axes(&self, point: &Point) -> (Option<f64>, Option<f64>) {
+        if !self.is_constraint_enabled(ConstraintType::SnapToGrid) {
+            return (None, None);
+        }
+        if let Some(frame) = self.frame_grids_at(point) {
+            return self.snap_to_frame_grids(frame, point);
+        }
+        if !self.grid.enabled || self.grid.size <= 0.0 {
+            return (None, None);
+        }
+
+        let size = self.grid.size;
+        (Some((point.x / size).round() * size), Some((point.y / size).round() * size))
+    }
+
+    /// Angled grids pull the point onto their nearest crossing; column, row and baseline
+    /// lines then take over per axis when one is within the snap threshold.
+    fn snap_to_frame_grids(&self, frame: &FrameGrids, point: &Point) -> (Option<f64>, Option<f64>) {
+        let grids: Vec<_> = frame.grids.iter().filter(|grid| grid.enabled).collect();
+        let crossing = grids
+            .iter()
+            .filter_map(|grid| grid.snap_to_lattice(&frame.bounds, point))
+            .min_by(|a, b| a.distance_to(point).total_cmp(&b.distance_to(point)));
+
+        let threshold = self.snap_threshold / self.viewport.zoom;
+        let (xs, ys): (Vec<_>, Vec<_>) = grids.iter().map(|grid| grid.nearest_lines(&frame.bounds, point)).unzip();
+        (
+            Self::nearest_line(xs.into_iter().flatten(), point.x, threshold).or(crossing.map(|p| p.x)),
+            Self::nearest_line(ys.into_iter().flatten(), point.y, threshold).or(crossing.map(|p| p.y)),
+        )
+    }
+
+    fn nearest_line(lines: impl Iterator<Item = f64>, value: f64, threshold: f64) -> Option<f64> {
+        lines
+            .filter(|line| (line - value).abs() <= threshold)
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+    }
+
+    /// The smallest move, per axis, that puts one of the box's edges or its center on
+    /// the grid. Corners, edge midpoints and the center are all tried, since a frame's
+    /// grid only covers the points inside it.
+    fn grid_snap_offset(&self, bounds: &BoundingBox) -> (f64, f64) {
+        let nearest = |offsets: Vec<f64>| offsets.into_iter().min_by(|a, b| a.abs().total_cmp(&b.abs())).unwrap_or(0.0);
+        let (mut xs, mut ys) = (Vec::new(), Vec::new());
+        for fx in [0.0, 0.5, 1.0] {
+            for fy in [0.0, 0.5, 1.0] {
+                let anchor = Point::new(bounds.x + bounds.width * fx, bounds.y + bounds.height * fy);
+                let (x, y) = self.grid_snap_axes(&anchor);
+                xs.extend(x.map(|x| x - anchor.x));
+                ys.extend(y.map(|y| y - anchor.y));
+            }
+        }
+        (nearest(xs), nearest(ys))
+    }
+
+    /// Looks up `element_ids` in `elements`, in id order.
+    fn find_elements(element_ids: &[String], elements: &[VectorElement]) -> Result<Vec<VectorElement>, TransformError> {
+        if element_ids.is_empty() {
+            return Err(TransformError::InvalidParameters("no elements to transform".to_string()));
+        }
+
+        element_ids
+            .iter()
+            .map(|id| {
+                elements
+                    .iter()
+                    .find(|el| el.id() == id)
+                    .cloned()
+                    .ok_or_else(|| TransformError::UnknownElement(id.clone()))
+            })
+            .collect()
+    }
+
+    /// Distance to move an edge at `position` by one nudge in direction `sign`. On a
+    /// snapping grid this lands on the next grid line rather than moving a fixed amount.
+    fn nudge_step(&self, position: f64, sign: f64, big: bool) -> f64 {
+        let count = if big { 10.0 } else { 1.0 };
+        if !self.grid.enabled || self.grid.size <= 0.0 || !self.is_constraint_enabled(ConstraintType::SnapToGrid) {
+            return sign * count;
+        }
+
+        let size = self.grid.size;
+        let line = position / size;
+        // An edge already on a line moves a whole cell, otherwise it first reaches the nearest line
+        let next = if sign > 0.0 {
+            (line + 1e-9).floor() + count
+        } else {
+            (line - 1e-9).ceil() - count
+        };
+        next * size - position
+    }
+
+    /// The rotation every element has, or `None` when they differ.
+    fn shared_rotation(elements: &[VectorElement]) -> Option<f64> {
+        let first = elements.first()?.transform().rotation;
+        elements
+            .iter()
+            .all(|el| (el.transform().rotation - first).abs() < 1e-9)
+            .then_some(first)
+    }
+
+    /// `set_transform_values` for one box: the whole selection, or a single element.
+    fn apply_transform_values(
+        &self,
+        elements: &[VectorElement],
+        values: &TransformValues,
+        reference: ReferencePoint,
+    ) -> Vec<VectorElement> {
+        let mut result = elements.to_vec();
+
+        if let (Some(rotation), Some(bounds)) = (values.rotation, Self::selection_bounds(&result)) {
+            // Mixed rotations count as zero, so the group turns by the typed angle
+            let angle = rotation - Self::shared_rotation(&result).unwrap_or(0.0);
+            let origin = reference.position(&bounds);
+            result = result.iter().map(|el| self.rotate_element(el, angle, &origin)).collect();
+        }
+
+        if let Some(bounds) = Self::selection_bounds(&result) {
+            let ratio = |target: Option<f64>, current: f64| match target {
+                Some(target) if current > 0.0 => Some(target / current),
+                _ => None,
+            };
+            let mut scale_x = ratio(values.width, bounds.width);
+            let mut scale_y = ratio(values.height, bounds.height);
+
+            if self.is_constraint_enabled(ConstraintType::MaintainAspect) {
+                if scale_x.is_some() {
+                    scale_y = scale_x;
+                } else {
+                    scale_x = scale_y;
+                }
+            }
+
+            if scale_x.is_some() || scale_y.is_some() {
+                let scale = Point::new(scale_x.unwrap_or(1.0), scale_y.unwrap_or(1.0));
+                let origin = reference.position(&bounds);
+                result = result.iter().map(|el| self.scale_element(el, &scale, &origin)).collect();
+            }
+        }
+
+        if let Some(bounds) = Self::selection_bounds(&result) {
+            let current = reference.position(&bounds);
+            let offset = Point::new(
+                values.x.map_or(0.0, |x| x - current.x),
+                values.y.map_or(0.0, |y| y - current.y),
+            );
+            if offset.x != 0.0 || offset.y != 0.0 {
+                result = result.iter().map(|el| self.translate_element(el, &offset)).collect();
+            }
+        }
+
+        result
+    }
+
+    fn begin_session(
+        &mut self,
+        action_type: TransformActionType,
+        element_ids: Vec<String>,
+        handle_type: HandleType,
+        start_point: Point,
+        document: &Document,
+    ) -> Result<(), TransformError> {
+        if self.session.is_some() {
+            return Err(TransformError::AlreadyTransforming);
+        }
+        self.nudge_run = None;
+
+        let elements = &document.elements;
+        let initial_elements = Self::find_elements(&element_ids, elements)?;
+        let bounds = Self::selection_bounds(&initial_elements)
+            .ok_or_else(|| TransformError::InvalidParameters("no elements to transform".to_string()))?;
+
+        // Everything else that is visible can be snapped to while dragging
+        let snap_targets = elements
+            .iter()
+            .filter(|el| el.is_visible() && !element_ids.iter().any(|id| id == el.id()))
+            .cloned()
+            .collect();
+        let shared_ancestors = Self::shared_ancestors(&initial_elements, elements);
+        let ruler_guides = Self::applicable_ruler_guides(&document.ruler_guides, &shared_ancestors);
+
+        // A pivot the user placed overrides the default origin for rotate and scale
+        let origin = match (&action_type, Self::selection_pivot(&initial_elements)) {
+            (TransformActionType::Rotate | TransformActionType::Scale, Some(pivot)) => pivot,
+            _ => handle_type.origin(&bounds),
+        };
+
+        self.session = Some(TransformSession {
+            action_type,
+            handle_type,
+            start_point,
+            origin,
+            initial_elements,
+            snap_targets,
+            ruler_guides,
+            delta: Transform::identity(),
+        });
+
+        Ok(())
+    }
+
+    /// Ids of the ancestors, nearest first, that every one of `moving` sits inside,
+    /// following parent links through `elements`.
+    fn shared_ancestors(moving: &[VectorElement], elements: &[VectorElement]) -> Vec<String> {
+        let ancestors = |element: &VectorElement| -> Vec<String> {
+            std::iter::successors(element.parent(), |id| {
+                elements.iter().find(|el| el.id() == *id).and_then(|el| el.parent())
+            })
+            // A parent cycle would never end, and no chain is longer than the document
+            .take(elements.len())
+            .map(str::to_string)
+            .collect()
+        };
+
+        let mut moving = moving.iter();
+        let mut shared = moving.next().map(ancestors).unwrap_or_default();
+        for element in moving {
+            let chain = ancestors(element);
+            shared.retain(|id| chain.contains(id));
+        }
+        shared
+    }
+
+    /// Turns the pointer movement since the drag started into a raw delta about the session origin.
+    fn pointer_delta(&self, session: &TransformSession, current_point: &Point) -> Transform {
+        let start = &session.start_point;
+        let origin = &session.origin;
+
+        if session.action_type == TransformActionType::Skew {
+            // The dragged edge slides along itself; its distance from the fixed edge
+            // turns the slide into an angle
+            return match session.handle_type {
+                HandleType::TopCenter | HandleType::BottomCenter => {
+                    let height = start.y - origin.y;
+                    if height.abs() < 1e-9 {
+                        return Transform::identity();
+                    }
+                    Transform::skew(((current_point.x - start.x) / height).atan(), 0.0)
+                }
+                _ => {
+                    let width = start.x - origin.x;
+                    if width.abs() < 1e-9 {
+                        return Transform::identity();
+                    }
+                    Transform::skew(0.0, ((current_point.y - start.y) / width).atan())
+                }
+            };
+        }
+
+        match session.handle_type {
+            HandleType::Center => Transform::translate(current_point.x - start.x, current_point.y - start.y),
+            HandleType::Rotation => {
+                let start_angle = (start.y - origin.y).atan2(start.x - origin.x);
+                let current_angle = (current_point.y - origin.y).atan2(current_point.x - origin.x);
+                Transform::rotate(current_angle - start_angle)
+            }
+            handle_type => {
+                // Edge handles sit on the origin's axis, so guard each axis separately
+                let denom_x = start.x - origin.x;
+                let denom_y = start.y - origin.y;
+                let scale_x = if denom_x.abs() < 1e-9 { 1.0 } else { (current_point.x - origin.x) / denom_x };
+                let scale_y = if denom_y.abs() < 1e-9 { 1.0 } else { (current_point.y - origin.y) / denom_y };
+
+                match handle_type {
+                    HandleType::TopCenter | HandleType::BottomCenter => Transform::scale(1.0, scale_y),
+                    HandleType::MiddleLeft | HandleType::MiddleRight => Transform::scale(scale_x, 1.0),
+                    _ => Transform::scale(scale_x, scale_y),
+                }
+            }
+        }
+    }
+
+    /// Snaps the moving selection's edges and center to those of `targets`, or to a position
+    /// that repeats a gap already used between its neighbours, independently per axis.
+    /// Only move sessions snap, so scaling and rotation stay exact even before the
+    /// pointer has moved.
+    fn apply_snapping(
+        &self,
+        action_type: &TransformActionType,
+        delta: &Transform,
+        elements: &[VectorElement],
+        targets: &[VectorElement],
+        ruler_guides: &[RulerGuide],
+    ) -> SnapResult {
+        let mut guides = Vec::new();
+        let mut spacing_guides = Vec::new();
+        let mut offset_x = 0.0;
+        let mut offset_y = 0.0;
+
+        let is_translation = *action_type == TransformActionType::Translate;
+        let moving = Self::selection_bounds(elements);
+
+        let snap_to_objects = self.is_constraint_enabled(ConstraintType::SnapToObject);
+        let snap_to_guides = self.is_constraint_enabled(ConstraintType::SnapToGuides);
+        let moving = match moving {
+            Some(bounds) if is_translation && (snap_to_objects || snap_to_guides) => {
+                BoundingBox::new(bounds.x + delta.translate_x, bounds.y + delta.translate_y, bounds.width, bounds.height)
+            }
+            _ => {
+                return SnapResult {
+                    snapped: false,
+                    position: Point::new(delta.translate_x, delta.translate_y),
+                    offset: Point::new(0.0, 0.0),
+                    guides,
+                    spacing_guides,
+                };
+            }
+        };
+
+        // The threshold is in screen pixels, so it shrinks in world units as we zoom in
+        let threshold = self.snap_threshold / self.viewport.zoom;
+        let moving_ids: Vec<String> = elements.iter().map(|el| el.id().to_string()).collect();
+        let targets = if snap_to_objects { targets } else { &[] };
+        let mut snapped = false;
+
+        for guide_type in [AlignmentType::Vertical, AlignmentType::Horizontal] {
+            let moving_lines = Self::snap_lines(&moving, &guide_type);
+            // Ruler guides are drawn already, so snapping to them adds no temporary guide
+            let ruler_lines = self.ruler_lines(ruler_guides, &guide_type);
+            let best = targets
+                .iter()
+                .flat_map(|target| Self::snap_lines(target.bounding_box(), &guide_type))
+                .chain(ruler_lines)
+                .flat_map(|target_line| moving_lines.iter().map(move |&line| target_line - line))
+                .filter(|offset| offset.abs() <= threshold)
+                .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+
+            let spacing = Self::spacing_snap(&moving, targets, &guide_type, threshold);
+
+            // Whichever candidate needs the smaller correction wins; alignment wins ties
+            let (offset, spaced_gap) = match (best, spacing) {
+                (alignment, Some((offset, gap))) if alignment.is_none_or(|a| offset.abs() < a.abs()) => (offset, Some(gap)),
+                (Some(offset), _) => (offset, None),
+                (None, _) => continue,
+            };
+
+            match guide_type {
+                AlignmentType::Vertical => offset_x = offset,
+                AlignmentType::Horizontal => offset_y = offset,
+            }
+            snapped = true;
+
+            if let Some(gap) = spaced_gap {
+                let moved = Self::offset_bounds(&moving, &guide_type, offset);
+                spacing_guides.push(Self::spacing_guide(&moved, &moving_ids, targets, &guide_type, gap));
+                continue;
+            }
+
+            // One guide per snapped line, listing every element that touches it
+            let mut positions: Vec<f64> = Vec::new();
+            for line in moving_lines.iter().map(|line| line + offset) {
+                let aligned: Vec<String> = targets
+                    .iter()
+                    .filter(|target| {
+                        Self::snap_lines(target.bounding_box(), &guide_type)
+                            .iter()
+                            .any(|target_line| (target_line - line).abs() < 1e-6)
+                    })
+                    .map(|target| target.id().to_string())
+                    .collect();
+
+                if aligned.is_empty() || positions.iter().any(|p| (p - line).abs() < 1e-6) {
+                    continue;
+                }
+                positions.push(line);
+
+                guides.push(AlignmentGuide {
+                    id: Uuid::new_v4().to_string(),
+                    guide_type: guide_type.clone(),
+                    position: line,
+                    elements: moving_ids.iter().cloned().chain(aligned).collect(),
+                    temporary: true,
+                });
+            }
+        }
+
+        SnapResult {
+            snapped,
+            position: Point::new(delta.translate_x + offset_x, delta.translate_y + offset_y),
+            offset: Point::new(offset_x, offset_y),
+            guides,
+            spacing_guides,
+        }
+    }
+
+    /// Looks for an offset along the axis that `guide_type` snaps on which leaves the moving
+    /// box centered between its neighbours, or repeats a gap already present between them.
+    /// Returns the closest one within `threshold` as `(offset, gap)`.
+    fn spacing_snap(moving: &BoundingBox, targets: &[VectorElement], guide_type: &AlignmentType, threshold: f64) -> Option<(f64, f64)> {
+        let (min, max, cross_min, cross_max) = Self::extent(moving, guide_type);
+        let size = max - min;
+
+        // Only elements sharing the moving box's row (or column) count as neighbours
+        let mut row: Vec<Extent> = targets
+            .iter()
+            .map(|target| Self::extent(target.bounding_box(), guide_type))
+            .filter(|&(_, _, c0, c1)| c0 < cross_max && c1 > cross_min)
+            .collect();
+        row.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let before = row
+            .iter()
+            .filter(|e| e.1 <= min + threshold && e.0 < min)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let after = row
+            .iter()
+            .filter(|e| e.0 >= max - threshold && e.1 > max)
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        let gaps: Vec<f64> = row
+            .windows(2)
+            .map(|pair| pair[1].0 - pair[0].1)
+            .filter(|gap| *gap > 0.0)
+            .collect();
+
+        let mut candidates = Vec::new();
+        if let (Some(before), Some(after)) = (before, after) {
+            let gap = (after.0 - before.1 - size) / 2.0;
+            if gap >= 0.0 {
+                candidates.push((before.1 + gap - min, gap));
+            }
+        }
+        for &gap in &gaps {
+            if let Some(before) = before {
+                candidates.push((before.1 + gap - min, gap));
+            }
+            if let Some(after) = after {
+                candidates.push((after.0 - gap - max, gap));
+            }
+        }
+
+        candidates
+            .into_iter()
+            .filter(|(offset, _)| offset.abs() <= threshold)
+            .min_by(|a, b| a.0.abs().total_cmp(&b.0.abs()))
+    }
+
+    /// Collects every gap equal to `gap` in the moved box's row (or column), including its own.
+    fn spacing_guide(moved: &BoundingBox, moving_ids: &[String], targets: &[VectorElement], guide_type: &AlignmentType, gap: f64) -> SpacingGuide {
+        let (_, _, cross_min, cross_max) = Self::extent(moved, guide_type);
+        let mut row: Vec<(Vec<String>, Extent)> = targets
+            .iter()
+            .map(|target| (vec![target.id().to_string()], Self::extent(target.bounding_box(), guide_type)))
+            .filter(|(_, (_, _, c0, c1))| *c0 < cross_max && *c1 > cross_min)
+            .collect();
+        row.push((moving_ids.to_vec(), Self::extent(moved, guide_type)));
+        row.sort_by(|a, b| a.1 .0.total_cmp(&b.1 .0));
+
+        let mut markers = Vec::new();
+        let mut elements: Vec<String> = Vec::new();
+        for pair in row.windows(2) {
+            let (ids_a, a) = &pair[0];
+            let (ids_b, b) = &pair[1];
+            if ((b.0 - a.1) - gap).abs() > 1e-6 {
+                continue;
+            }
+
+            markers.push(GapMarker {
+                start: a.1,
+                end: b.0,
+                cross: (a.2.max(b.2) + a.3.min(b.3)) / 2.0,
+            });
+            for id in ids_a.iter().chain(ids_b) {
+                if !elements.contains(id) {
+                    elements.push(id.clone());
+                }
+            }
+        }
+
+        SpacingGuide {
+            id: Uuid::new_v4().to_string(),
+            axis: Self::spacing_axis(guide_type),
+            gap,
+            markers,
+            elements,
+            temporary: true,
+        }
+    }
+
+    /// The box's extent along the axis `guide_type` snaps on.
+    fn extent(bounds: &BoundingBox, guide_type: &AlignmentType) -> Extent {
+        match guide_type {
+            AlignmentType::Vertical => (bounds.x, bounds.x + bounds.width, bounds.y, bounds.y + bounds.height),
+            AlignmentType::Horizontal => (bounds.y, bounds.y + bounds.height, bounds.x, bounds.x + bounds.width),
+        }
+    }
+
+    fn offset_bounds(bounds: &BoundingBox, guide_type: &AlignmentType, offset: f64) -> BoundingBox {
+        match guide_type {
+            AlignmentType::Vertical => BoundingBox::new(bounds.x + offset, bounds.y, bounds.width, bounds.height),
+            AlignmentType::Horizontal => BoundingBox::new(bounds.x, bounds.y + offset, bounds.width, bounds.height),
+        }
+    }
+
+    /// Vertical guide lines sit at x positions, so they pair with gaps measured along x.
+    fn spacing_axis(guide_type: &AlignmentType) -> SpacingAxis {
+        match guide_type {
+            AlignmentType::Vertical => SpacingAxis::Horizontal,
+            AlignmentType::Horizontal => SpacingAxis::Vertical,
+        }
+    }
+
+    /// The lines a box can snap along: left/center/right for vertical guides,
+    /// top/middle/bottom for horizontal ones.
+    fn snap_lines(bounds: &BoundingBox, guide_type: &AlignmentType) -> [f64; 3] {
+        match guide_type {
+            AlignmentType::Vertical => [bounds.x, bounds.x + bounds.width / 2.0, bounds.x + bounds.width],
+            AlignmentType::Horizontal => [bounds.y, bounds.y + bounds.height / 2.0, bounds.y + bounds.height],
+        }
+    }
+
+    fn apply_constraints(&self, action_type: &TransformActionType, delta: &Transform, elements: &[VectorElement]) -> Transform {
+        let mut constrained_delta = delta.clone();
+        let scaled = delta.scale_x != 1.0 || delta.scale_y != 1.0;
+
+        // Maintain aspect ratio, following whichever axis moved further and keeping flips.
+        // A `ratio` param fixes width / height instead of keeping the starting proportions
+        if let Some(constraint) = self.enabled_constraint(ConstraintType::MaintainAspect).filter(|_| scaled) {
+            let follow_x = (delta.scale_x.abs() - 1.0).abs() >= (delta.scale_y.abs() - 1.0).abs();
+            let ratio = constraint.param_f64("ratio").or_else(|| {
+                Some(constraint.param_f64("width")? / constraint.param_f64("height")?)
+            });
+
+            match (ratio.filter(|r| r.is_finite() && *r > 0.0), Self::selection_bounds(elements)) {
+                (Some(ratio), Some(bounds)) if bounds.width > 0.0 && bounds.height > 0.0 => {
+                    if follow_x {
+                        let height = bounds.width * delta.scale_x.abs() / ratio;
+                        constrained_delta.scale_y = (height / bounds.height).copysign(delta.scale_y);
+                    } else {
+                        let width = bounds.height * delta.scale_y.abs() * ratio;
+                        constrained_delta.scale_x = (width / bounds.width).copysign(delta.scale_x);
+                    }
+                }
+                _ => {
+                    let uniform = if follow_x { delta.scale_x.abs() } else { delta.scale_y.abs() };
+                    constrained_delta.scale_x = uniform.copysign(delta.scale_x);
+                    constrained_delta.scale_y = uniform.copysign(delta.scale_y);
+                }
+            }
+        }
+
+        // Scale limits, as factors of the size the drag started from. While the aspect is
+        // maintained both axes are scaled back by one factor, so the ratio survives
+        if let Some(constraint) = self.enabled_constraint(ConstraintType::ScaleLimits) {
+            let min = constraint.param_f64("min").unwrap_or(0.0);
+            let max = constraint.param_f64("max").unwrap_or(f64::INFINITY);
+            if min <= max && scaled && self.is_constraint_enabled(ConstraintType::MaintainAspect) {
+                let (scale_x, scale_y) = (constrained_delta.scale_x.abs(), constrained_delta.scale_y.abs());
+                let lower = (min / scale_x).max(min / scale_y);
+                let upper = (max / scale_x).min(max / scale_y);
+                // When the axes are too far apart for both to fit, the max limit wins
+                let factor = 1.0_f64.max(lower).min(upper);
+                constrained_delta.scale_x *= factor;
+                constrained_delta.scale_y *= factor;
+            } else if min <= max {
+                constrained_delta.scale_x = constrained_delta.scale_x.abs().clamp(min, max).copysign(constrained_delta.scale_x);
+                constrained_delta.scale_y = constrained_delta.scale_y.abs().clamp(min, max).copysign(constrained_delta.scale_y);
+            }
+        }
+
+        // Lock rotation
+        if self.is_constraint_enabled(ConstraintType::LockRotation) {
+            constrained_delta.rotation = 0.0;
+        }
+
+        // Snap rotation to increments
+        if let Some(constraint) = self.enabled_constraint(ConstraintType::SnapRotation) {
+            let increment = constraint.param_f64("increment").unwrap_or(15.0).to_radians();
+            if increment > 0.0 {
+                constrained_delta.rotation = (constrained_delta.rotation / increment).round() * increment;
+            }
+        }
+
+        // Lock scale
+        if self.is_constraint_enabled(ConstraintType::LockScale) {
+            constrained_delta.scale_x = 1.0;
+            constrained_delta.scale_y = 1.0;
+        }
+
+        // Lock translation to one axis
+        if let Some(constraint) = self.enabled_constraint(ConstraintType::LockAxis) {
+            let horizontal = match constraint.param_str("axis") {
+                Some("x") => true,
+                Some("y") => false,
+                _ => delta.translate_x.abs() >= delta.translate_y.abs(),
+            };
+            if horizontal {
+                constrained_delta.translate_y = 0.0;
+            } else {
+                constrained_delta.translate_x = 0.0;
+            }
+        }
+
+        // Snap to grid: on each axis, whichever of the moved selection's edges or center
+        // is nearest a grid line lands on it
+        let moving = *action_type == TransformActionType::Translate;
+        if let (true, Some(bounds)) = (moving && self.is_constraint_enabled(ConstraintType::SnapToGrid), Self::selection_bounds(elements)) {
+            let moved = BoundingBox::new(
+                bounds.x + constrained_delta.translate_x,
+                bounds.y + constrained_delta.translate_y,
+                bounds.width,
+                bounds.height,
+            );
+            let (offset_x, offset_y) = self.grid_snap_offset(&moved);
+            constrained_delta.translate_x += offset_x;
+            constrained_delta.translate_y += offset_y;
+        }
+
+        constrained_delta
+    }
+
+    fn add_transforms(&self, a: &Transform, b: &Transform) -> Transform {
+        Transform {
+            translate_x: a.translate_x + b.translate_x,
+            translate_y: a.translate_y + b.translate_y,
+            scale_x: a.scale_x * b.scale_x,
+            scale_y: a.scale_y * b.scale_y,
+            rotation: a.rotation + b.rotation,
+            skew_x: a.skew_x + b.skew_x,
+            skew_y: a.skew_y + b.skew_y,
+        }
+    }
+
+    fn transform_bounds(&self, bounds: &BoundingBox, matrix: &Matrix3) -> BoundingBox {
+        // Transform the four corners of the bounding box
+        let corners = [
+            (bounds.x, bounds.y),
+            (bounds.x + bounds.width, bounds.y),
+            (bounds.x + bounds.width, bounds.y + bounds.height),
+            (bounds.x, bounds.y + bounds.height),
+        ];
+
+        let transformed_corners: Vec<(f64, f64)> = corners
+            .iter()
+            .map(|&(x, y)| matrix.transform_coords(x, y))
+            .collect();
+        
+        let min_x = transformed_corners.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let min_y = transformed_corners.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_x = transformed_corners.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+        let max_y = transformed_corners.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+
+        BoundingBox::new(min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    fn clear_temporary_guides(&mut self) {
+        self.alignment_guides.retain(|guide| !guide.temporary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transform::{GridKind, LayoutGrid};
+    use crate::core::vector::{FontStyle, FontWeight, Style, TextAlign, VectorShape, VectorText};
+    use std::f64::consts::FRAC_PI_2;
+
+    fn rect(id: &str, x: f64, y: f64, width: f64, height: f64) -> VectorElement {
+        VectorElement::Shape {
+            id: id.to_string(),
+            transform: Transform::translate(x, y),
+            style: Style { fill: None, stroke: None, shadow: None, opacity: None },
+            bounding_box: BoundingBox::new(x, y, width, height),
+            visible: true,
+            locked: false,
+            z_index: 0,
+            shape: VectorShape::Rectangle { width, height },
+            parent: None,
+            pivot: None,
+        }
+    }
+
+    fn document(elements: &[VectorElement]) -> Document {
+        Document { elements: elements.to_vec(), ruler_guides: Vec::new() }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    fn handle<'a>(handles: &'a [SelectionHandle], id: &str) -> &'a SelectionHandle {
+        handles.iter().find(|handle| handle.id == id).unwrap()
+    }
+
+    #[test]
+    fn handles_follow_a_rotated_element() {
+        let engine = TransformEngine::new();
+        let element = rect("a", 100.0, 100.0, 40.0, 20.0);
+        let rotated = engine.rotate_element(&element, FRAC_PI_2, &Point::new(100.0, 100.0));
+
+        let selection = Selection::from_elements(std::slice::from_ref(&rotated)).unwrap();
+        assert!(close(selection.bounds.width, 40.0) && close(selection.bounds.height, 20.0));
+
+        let handles = engine.selection_handles(&[rotated]);
+        let top_left = handle(&handles, "nw");
+        let bottom_right = handle(&handles, "se");
+        assert!(close(top_left.position.x, 100.0) && close(top_left.position.y, 100.0));
+        assert!(close(bottom_right.position.x, 80.0) && close(bottom_right.position.y, 140.0));
+        assert_eq!(top_left.cursor, "ne-resize");
+    }
+
+    #[test]
+    fn handles_follow_a_scaled_and_rotated_element() {
+        let engine = TransformEngine::new();
+        let element = rect("a", 0.0, 0.0, 40.0, 20.0);
+        let scaled = engine.scale_element(&element, &Point::new(2.0, 1.0), &Point::new(0.0, 0.0));
+        let rotated = engine.rotate_element(&scaled, FRAC_PI_2 / 3.0, &Point::new(0.0, 0.0));
+
+        let selection = Selection::from_elements(std::slice::from_ref(&rotated)).unwrap();
+        let frame = selection.transform.to_matrix();
+        let corners = [
+            (selection.bounds.x, selection.bounds.y),
+            (selection.bounds.x + selection.bounds.width, selection.bounds.y + selection.bounds.height),
+        ];
+        let (x0, y0) = frame.transform_coords(corners[0].0, corners[0].1);
+        let (x1, y1) = frame.transform_coords(corners[1].0, corners[1].1);
+        let (side_x, side_y) = frame.transform_vector(selection.bounds.width, 0.0);
+
+        assert!(close(x0, 0.0) && close(y0, 0.0));
+        assert!(close(side_x.hypot(side_y), 80.0));
+        assert!(close((x1 - x0).hypot(y1 - y0), 80.0_f64.hypot(20.0)));
+    }
+
+    #[test]
+    fn several_elements_get_canvas_aligned_handles() {
+        let engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 20.0, 5.0, 10.0, 10.0)];
+
+        let selection = Selection::from_elements(&elements).unwrap();
+        assert_eq!(selection.transform, Transform::identity());
+        assert_eq!(selection.bounds, BoundingBox::new(0.0, 0.0, 30.0, 15.0));
+
+        let handles = engine.selection_handles(&elements);
+        let bottom_right = handle(&handles, "se");
+        assert!(close(bottom_right.position.x, 30.0) && close(bottom_right.position.y, 15.0));
+        assert!(engine.selection_handles(&[]).is_empty());
+    }
+
+    #[test]
+    fn repeated_nudges_merge_into_one_action() {
+        let mut engine = TransformEngine::new();
+        let ids = vec!["a".to_string()];
+        let elements = vec![rect("a", 3.0, 3.0, 10.0, 10.0)];
+
+        let first = engine.nudge(&ids, NudgeDirection::Right, false, &elements).unwrap();
+        let second = engine.nudge(&ids, NudgeDirection::Up, true, &first.elements).unwrap();
+        assert!(!first.merged && second.merged);
+        assert!(close(second.action.delta.translate_x, 1.0) && close(second.action.delta.translate_y, -10.0));
+        assert_eq!(second.elements[0].bounding_box(), &BoundingBox::new(4.0, -7.0, 10.0, 10.0));
+
+        // Elements changed elsewhere, or a released key, start a new entry
+        let moved = vec![rect("a", 50.0, 50.0, 10.0, 10.0)];
+        assert!(!engine.nudge(&ids, NudgeDirection::Left, false, &moved).unwrap().merged);
+        engine.end_nudge();
+        assert!(!engine.nudge(&ids, NudgeDirection::Left, false, &second.elements).unwrap().merged);
+    }
+
+    #[test]
+    fn rotation_nudges_keep_the_bounds_tight() {
+        let mut engine = TransformEngine::new();
+        let ids = vec!["a".to_string()];
+        let mut elements = vec![rect("a", 0.0, 0.0, 10.0, 10.0)];
+
+        for _ in 0..6 {
+            elements = engine.nudge_with_mode(&ids, NudgeDirection::Right, true, NudgeMode::Rotate, &elements).unwrap().elements;
+        }
+        let result = engine.nudge_with_mode(&ids, NudgeDirection::Right, true, NudgeMode::Rotate, &elements).unwrap();
+        assert!(result.merged && close(result.action.delta.rotation, 105f64.to_radians()));
+
+        // Six 15° steps make a quarter turn, which leaves the square's box where it was
+        let bounds = elements[0].bounding_box();
+        assert!(close(bounds.x, 0.0) && close(bounds.y, 0.0), "{:?}", bounds);
+        assert!(close(bounds.width, 10.0) && close(bounds.height, 10.0), "{:?}", bounds);
+    }
+
+    #[test]
+    fn scale_nudges_step_from_the_current_size() {
+        let mut engine = TransformEngine::new();
+        let ids = vec!["a".to_string()];
+        let mut elements = vec![rect("a", 0.0, 0.0, 20.0, 10.0)];
+
+        for _ in 0..3 {
+            elements = engine.nudge_with_mode(&ids, NudgeDirection::Up, true, NudgeMode::Scale, &elements).unwrap().elements;
+        }
+        let bounds = elements[0].bounding_box();
+        assert!(close(bounds.width, 50.0) && close(bounds.height, 25.0), "{:?}", bounds);
+        assert!(close(bounds.center().x, 10.0) && close(bounds.center().y, 5.0));
+    }
+
+    #[test]
+    fn nudges_step_to_grid_lines_while_snapping() {
+        let mut engine = TransformEngine::new();
+        engine.set_grid(true, 20.0, "#000".to_string(), 1.0, true);
+        let ids = vec!["a".to_string()];
+        let elements = vec![rect("a", 5.0, 5.0, 10.0, 10.0)];
+
+        let right = engine.nudge(&ids, NudgeDirection::Right, false, &elements).unwrap();
+        assert!(close(right.elements[0].bounding_box().x, 20.0));
+        let right = engine.nudge(&ids, NudgeDirection::Right, false, &right.elements).unwrap();
+        assert!(close(right.elements[0].bounding_box().x, 40.0) && right.merged);
+        engine.end_nudge();
+
+        let left = engine.nudge(&ids, NudgeDirection::Left, false, &elements).unwrap();
+        assert!(close(left.elements[0].bounding_box().x, 0.0));
+        engine.end_nudge();
+        let down = engine.nudge(&ids, NudgeDirection::Down, true, &elements).unwrap();
+        assert!(close(down.elements[0].bounding_box().y, 200.0));
+
+        // Without grid snapping the steps are plain pixels
+        engine.set_grid(true, 20.0, "#000".to_string(), 1.0, false);
+        engine.end_nudge();
+        let plain = engine.nudge(&ids, NudgeDirection::Left, true, &elements).unwrap();
+        assert!(close(plain.elements[0].bounding_box().x, -5.0));
+    }
+
+    #[test]
+    fn frame_grids_follow_the_snap_to_grid_constraint() {
+        let mut engine = TransformEngine::new();
+        engine.set_frame_grids(FrameGrids {
+            frame_id: "frame".to_string(),
+            bounds: BoundingBox::new(0.0, 0.0, 400.0, 300.0),
+            grids: vec![LayoutGrid { enabled: true, kind: GridKind::Baseline { height: 8.0, offset: 0.0 } }],
+        });
+        let point = Point::new(50.0, 23.0);
+        assert_eq!(engine.snap_to_grid(&point), point);
+
+        engine.set_constraint(ConstraintType::SnapToGrid, true);
+        assert_eq!(engine.snap_to_grid(&point), Point::new(50.0, 24.0));
+
+        // Outside the frame only the canvas grid applies, and only while it is shown
+        let outside = Point::new(450.0, 23.0);
+        assert_eq!(engine.snap_to_grid(&outside), outside);
+        engine.set_grid(true, 20.0, "#000".to_string(), 1.0, true);
+        assert_eq!(engine.snap_to_grid(&outside), Point::new(460.0, 20.0));
+
+        // The grid settings' snap flag and the constraint are one switch
+        engine.set_grid(true, 20.0, "#000".to_string(), 1.0, false);
+        assert!(!engine.is_constraint_enabled(ConstraintType::SnapToGrid));
+        assert_eq!(engine.snap_to_grid(&point), point);
+        assert_eq!(engine.snap_to_grid(&outside), outside);
+        engine.set_constraint(ConstraintType::SnapToGrid, true);
+        assert!(engine.grid.snap);
+        assert_eq!(engine.snap_to_grid(&outside), Point::new(460.0, 20.0));
+    }
+
+    #[test]
+    fn grid_snapping_uses_the_nearest_edge_or_center() {
+        let mut engine = TransformEngine::new();
+        engine.set_constraint(ConstraintType::SnapToObject, false);
+        engine.set_grid(true, 20.0, "#000".to_string(), 1.0, true);
+        let elements = [rect("a", 0.0, 0.0, 30.0, 30.0)];
+
+        // Moved 7 right, the center (at 22) is closer to a line than either side
+        engine.start_transform(vec!["a".to_string()], HandleType::Center, Point::new(0.0, 0.0), &document(&elements)).unwrap();
+        let update = engine.update_transform(Point::new(7.0, 0.0)).unwrap();
+        assert_eq!(update.elements[0].bounding_box(), &BoundingBox::new(5.0, 0.0, 30.0, 30.0));
+        engine.cancel_transform();
+
+        // The right edge wins when it is closest
+        engine.start_transform(vec!["a".to_string()], HandleType::Center, Point::new(0.0, 0.0), &document(&elements)).unwrap();
+        let update = engine.update_transform(Point::new(11.0, 0.0)).unwrap();
+        assert_eq!(update.elements[0].bounding_box().x, 10.0);
+        engine.cancel_transform();
+
+        // A resize that has not changed anything yet is not moved onto the grid
+        let off_grid = [rect("b", 3.0, 3.0, 10.0, 10.0)];
+        engine.start_transform(vec!["b".to_string()], HandleType::BottomRight, Point::new(13.0, 13.0), &document(&off_grid)).unwrap();
+        let update = engine.update_transform(Point::new(13.0, 13.0)).unwrap();
+        assert_eq!(update.elements[0].bounding_box(), off_grid[0].bounding_box());
+    }
+
+    fn child_of(mut element: VectorElement, id: &str) -> VectorElement {
+        if let VectorElement::Shape { parent, .. } = &mut element {
+            *parent = Some(id.to_string());
+        }
+        element
+    }
+
+    fn drag_x(engine: &mut TransformEngine, id: &str, distance: f64, document: &Document) -> f64 {
+        engine.start_transform(vec![id.to_string()], HandleType::Center, Point::new(0.0, 0.0), document).unwrap();
+        let update = engine.update_transform(Point::new(distance, 0.0)).unwrap();
+        engine.cancel_transform();
+        update.elements[0].bounding_box().x
+    }
+
+    #[test]
+    fn artboard_guides_reach_into_nested_groups() {
+        let mut engine = TransformEngine::new();
+        engine.set_constraint(ConstraintType::SnapToObject, false);
+        let mut document = document(&[
+            rect("board", 0.0, 0.0, 500.0, 500.0),
+            child_of(rect("group", 0.0, 200.0, 10.0, 10.0), "board"),
+            child_of(rect("nested", 0.0, 200.0, 10.0, 10.0), "group"),
+            rect("loose", 0.0, 300.0, 10.0, 10.0),
+        ]);
+        TransformEngine::add_ruler_guide(&mut document, AlignmentType::Vertical, 100.0, Some("board".to_string()));
+
+        assert!(close(drag_x(&mut engine, "nested", 98.0, &document), 100.0));
+        assert!(close(drag_x(&mut engine, "group", 98.0, &document), 100.0));
+        assert!(close(drag_x(&mut engine, "loose", 98.0, &document), 98.0));
+    }
+
+    #[test]
+    fn ruler_guides_are_edited_on_the_document() {
+        let mut document = document(&[rect("a", 0.0, 0.0, 10.0, 10.0)]);
+        let guide = TransformEngine::add_ruler_guide(&mut document, AlignmentType::Horizontal, 40.0, None);
+        assert_eq!(document.ruler_guides, vec![guide.clone()]);
+        assert!(!guide.locked);
+
+        TransformEngine::move_ruler_guide(&mut document, &guide.id, 55.0).unwrap();
+        assert_eq!(document.ruler_guides[0].position, 55.0);
+
+        // Drags started on the document snap to the guide where it now is
+        let mut engine = TransformEngine::new();
+        engine.start_transform(vec!["a".to_string()], HandleType::Center, Point::new(0.0, 0.0), &document).unwrap();
+        let update = engine.update_transform(Point::new(0.0, 53.0)).unwrap();
+        assert!(close(update.elements[0].bounding_box().y, 55.0));
+        engine.cancel_transform();
+
+        TransformEngine::delete_ruler_guide(&mut document, &guide.id).unwrap();
+        assert!(document.ruler_guides.is_empty());
+        assert_eq!(
+            TransformEngine::delete_ruler_guide(&mut document, &guide.id),
+            Err(TransformError::UnknownGuide(guide.id.clone()))
+        );
+    }
+
+    #[test]
+    fn locked_ruler_guides_cannot_be_moved_or_deleted() {
+        let mut document = Document::default();
+        let id = TransformEngine::add_ruler_guide(&mut document, AlignmentType::Vertical, 100.0, None).id;
+        TransformEngine::set_ruler_guide_locked(&mut document, &id, true).unwrap();
+
+        let locked = Err(TransformError::GuideLocked(id.clone()));
+        assert_eq!(TransformEngine::move_ruler_guide(&mut document, &id, 120.0), locked);
+        assert_eq!(TransformEngine::delete_ruler_guide(&mut document, &id), locked);
+        assert_eq!(document.ruler_guides[0].position, 100.0);
+
+        // Unlocking is always allowed, and frees the guide again
+        TransformEngine::set_ruler_guide_locked(&mut document, &id, false).unwrap();
+        TransformEngine::move_ruler_guide(&mut document, &id, 120.0).unwrap();
+        assert_eq!(document.ruler_guides[0].position, 120.0);
+        assert_eq!(
+            TransformEngine::set_ruler_guide_locked(&mut document, "missing", true),
+            Err(TransformError::UnknownGuide("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn pixel_snapped_moves_keep_the_size() {
+        let mut engine = TransformEngine::new();
+        engine.set_constraint(ConstraintType::SnapToObject, false);
+        engine.set_pixel_snapping(true, 1.0);
+        let elements = [rect("a", 0.0, 0.0, 10.3, 10.3)];
+
+        for distance in [0.4, 0.1, 2.7] {
+            engine.start_transform(vec!["a".to_string()], HandleType::Center, Point::new(0.0, 0.0), &document(&elements)).unwrap();
+            let update = engine.update_transform(Point::new(distance, distance)).unwrap();
+            let bounds = update.elements[0].bounding_box();
+            assert!(close(bounds.x, distance.round()) && close(bounds.y, distance.round()));
+            assert!(close(bounds.width, 10.3) && close(bounds.height, 10.3));
+
+            // History replays exactly what the drag showed
+            let action = engine.finish_transform().unwrap();
+            let replayed = engine.transform_element(&elements[0], &action.delta, &action.origin);
+            assert_eq!(replayed.bounding_box(), bounds);
+        }
+    }
+
+    #[test]
+    fn pixel_snapped_resizes_land_both_edges_on_pixels() {
+        let mut engine = TransformEngine::new();
+        engine.set_pixel_snapping(true, 1.0);
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0)];
+
+        engine.start_transform(vec!["a".to_string()], HandleType::BottomRight, Point::new(10.0, 10.0), &document(&elements)).unwrap();
+        let update = engine.update_transform(Point::new(13.3, 12.6)).unwrap();
+        let bounds = update.elements[0].bounding_box();
+        assert!(close(bounds.x, 0.0) && close(bounds.y, 0.0));
+        assert!(close(bounds.width, 13.0) && close(bounds.height, 13.0));
+
+        let action = engine.finish_transform().unwrap();
+        let replayed = engine.transform_element(&elements[0], &action.delta, &action.origin);
+        let replayed = replayed.bounding_box();
+        assert!(close(replayed.width, 13.0) && close(replayed.height, 13.0));
+    }
+
+    #[test]
+    fn pixel_grid_follows_the_export_unless_asked_for_the_screen() {
+        let mut engine = TransformEngine::new();
+        engine.set_viewport(Viewport::new(0.0, 0.0, 2.0, 800.0, 600.0));
+        engine.set_pixel_snapping(true, 1.0);
+        assert_eq!(engine.pixel_size(), 1.0);
+
+        let elements = [rect("a", 0.4, 0.3, 10.0, 10.0)];
+        assert_eq!(engine.snap_to_pixels(&elements)[0].bounding_box().x, 0.0);
+
+        engine.set_pixel_grid(PixelGrid::Screen);
+        assert_eq!(engine.pixel_size(), 0.5);
+        assert_eq!(engine.snap_to_pixels(&elements)[0].bounding_box().x, 0.5);
+    }
+
+    fn snap(engine: &TransformEngine, moving: &VectorElement, by: Point, targets: &[VectorElement]) -> SnapResult {
+        let delta = Transform::translate(by.x, by.y);
+        engine.apply_snapping(&TransformActionType::Translate, &delta, std::slice::from_ref(moving), targets, &[])
+    }
+
+    #[test]
+    fn edges_snap_to_nearby_edges() {
+        let engine = TransformEngine::new();
+        let moving = rect("a", 0.0, 0.0, 10.0, 10.0);
+        let targets = [rect("b", 60.0, 100.0, 10.0, 10.0)];
+
+        let result = snap(&engine, &moving, Point::new(47.0, 0.0), &targets);
+        assert!(result.snapped);
+        assert_eq!(result.offset, Point::new(3.0, 0.0));
+        assert_eq!(result.position, Point::new(50.0, 0.0));
+        assert_eq!(result.guides.len(), 1);
+        assert_eq!(result.guides[0].guide_type, AlignmentType::Vertical);
+        assert_eq!(result.guides[0].position, 60.0);
+        assert_eq!(result.guides[0].elements, ["a", "b"]);
+
+        let result = snap(&engine, &moving, Point::new(40.0, 0.0), &targets);
+        assert!(!result.snapped && result.guides.is_empty());
+    }
+
+    #[test]
+    fn centers_snap_to_nearby_centers() {
+        let engine = TransformEngine::new();
+        let moving = rect("a", 0.0, 0.0, 10.0, 10.0);
+        let targets = [rect("b", 100.0, 50.0, 20.0, 20.0)];
+
+        let result = snap(&engine, &moving, Point::new(103.0, 0.0), &targets);
+        assert_eq!(result.offset, Point::new(2.0, 0.0));
+        assert_eq!(result.guides.len(), 1);
+        assert_eq!(result.guides[0].position, 110.0);
+    }
+
+    #[test]
+    fn snap_threshold_is_in_screen_pixels() {
+        let mut engine = TransformEngine::new();
+        let moving = rect("a", 0.0, 0.0, 40.0, 10.0);
+        let targets = [rect("b", 60.0, 100.0, 10.0, 10.0)];
+        let by = Point::new(17.0, 0.0);
+        assert_eq!(snap(&engine, &moving, by, &targets).offset, Point::new(3.0, 0.0));
+
+        // Zoomed in, 5 screen pixels cover only 2.5 canvas units
+        engine.set_viewport(Viewport::new(0.0, 0.0, 2.0, 800.0, 600.0));
+        assert!(!snap(&engine, &moving, by, &targets).snapped);
+
+        engine.set_viewport(Viewport::new(0.0, 0.0, 1.0, 800.0, 600.0));
+        engine.set_snap_threshold(2.0);
+        assert!(!snap(&engine, &moving, by, &targets).snapped);
+    }
+
+    #[test]
+    fn only_move_sessions_snap() {
+        let mut engine = TransformEngine::new();
+        let moving = rect("a", 0.0, 0.0, 10.0, 10.0);
+        let targets = [rect("b", 11.0, 0.0, 10.0, 10.0)];
+        let identity = Transform::identity();
+        let selection = std::slice::from_ref(&moving);
+        assert!(engine.apply_snapping(&TransformActionType::Translate, &identity, selection, &targets, &[]).snapped);
+        for action_type in [TransformActionType::Scale, TransformActionType::Rotate, TransformActionType::Skew] {
+            assert!(!engine.apply_snapping(&action_type, &identity, selection, &targets, &[]).snapped);
+        }
+
+        // The first update of a resize drag leaves the selection where it was
+        let elements = [moving.clone(), targets[0].clone()];
+        let start = Point::new(10.0, 10.0);
+        engine.start_transform(vec!["a".to_string()], HandleType::BottomRight, start, &document(&elements)).unwrap();
+        let update = engine.update_transform(start).unwrap();
+        assert_eq!(update.elements[0].bounding_box(), moving.bounding_box());
+        assert!(update.guides.is_empty());
+    }
+
+    #[test]
+    fn spacing_snaps_to_equal_gaps() {
+        let engine = TransformEngine::new();
+        let moving = rect("m", 23.0, 200.0, 10.0, 10.0);
+        let targets = [rect("a", 0.0, 0.0, 10.0, 10.0), rect("c", 40.0, 0.0, 10.0, 10.0)];
+
+        // Centered between its neighbours the gaps are 10 on both sides
+        let result = snap(&engine, &moving, Point::new(0.0, -200.0), &targets);
+        assert_eq!(result.offset, Point::new(-3.0, 0.0));
+        assert_eq!(result.spacing_guides.len(), 1);
+        let guide = &result.spacing_guides[0];
+        assert_eq!(guide.axis, SpacingAxis::Horizontal);
+        assert_eq!(guide.gap, 10.0);
+        assert_eq!(guide.markers.len(), 2);
+        assert!(result.guides.iter().all(|guide| guide.guide_type == AlignmentType::Horizontal));
+
+        // Past the last neighbour the existing gap repeats
+        let moving = rect("m", 62.0, 200.0, 10.0, 10.0);
+        let targets = [rect("a", 0.0, 0.0, 10.0, 10.0), rect("c", 30.0, 0.0, 10.0, 10.0)];
+        let result = snap(&engine, &moving, Point::new(0.0, -200.0), &targets);
+        assert_eq!(result.offset.x, -2.0);
+        assert_eq!(result.spacing_guides[0].gap, 20.0);
+    }
+
+    #[test]
+    fn spacing_ignores_elements_outside_the_row() {
+        let engine = TransformEngine::new();
+        let moving = rect("m", 23.0, 200.0, 10.0, 10.0);
+        let targets = [rect("a", 0.0, 50.0, 10.0, 10.0), rect("c", 40.0, 50.0, 10.0, 10.0)];
+        let result = snap(&engine, &moving, Point::new(0.0, -200.0), &targets);
+        assert!(result.spacing_guides.is_empty());
+        assert_eq!(result.offset.x, 0.0);
+    }
+
+    #[test]
+    fn distribute_spaces_centers_and_gaps() {
+        let engine = TransformEngine::new();
+        // Listed out of order; the results come back in input order
+        let elements = [rect("c", 100.0, 0.0, 20.0, 10.0), rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 30.0, 0.0, 40.0, 10.0)];
+
+        // Centers at 5 and 110 put the middle one's center at 57.5
+        let centers = engine.distribute_elements(&elements, &SpacingAxis::Horizontal, &Distribution::Centers);
+        assert_eq!(centers[..2], elements[..2]);
+        assert!(close(centers[2].bounding_box().center().x, 57.5));
+
+        // 120 wide with 70 of elements leaves two 25 gaps
+        let gaps = engine.distribute_elements(&elements, &SpacingAxis::Horizontal, &Distribution::Gaps);
+        assert_eq!(gaps[..2], elements[..2]);
+        assert_eq!(gaps[2].bounding_box(), &BoundingBox::new(35.0, 0.0, 40.0, 10.0));
+
+        // Two elements have nothing to spread between them
+        let pair = engine.distribute_elements(&elements[..2], &SpacingAxis::Horizontal, &Distribution::Gaps);
+        assert_eq!(pair, elements[..2]);
+    }
+
+    #[test]
+    fn fixed_gap_distribution_starts_from_the_first_element() {
+        let engine = TransformEngine::new();
+        let elements = [rect("b", 0.0, 50.0, 10.0, 30.0), rect("a", 0.0, 0.0, 10.0, 20.0), rect("c", 5.0, 60.0, 10.0, 5.0)];
+
+        let result = engine.distribute_elements(&elements, &SpacingAxis::Vertical, &Distribution::FixedGap { gap: 8.0 });
+        assert_eq!(result[1], elements[1]);
+        assert_eq!(result[0].bounding_box(), &BoundingBox::new(0.0, 28.0, 10.0, 30.0));
+        assert_eq!(result[2].bounding_box(), &BoundingBox::new(5.0, 66.0, 10.0, 5.0));
+
+        let pair = engine.distribute_elements(&elements[..2], &SpacingAxis::Vertical, &Distribution::FixedGap { gap: 0.0 });
+        assert_eq!(pair[0].bounding_box(), &BoundingBox::new(0.0, 20.0, 10.0, 30.0));
+    }
+
+    #[test]
+    fn tidy_up_lines_rows_and_columns_up() {
+        let engine = TransformEngine::new();
+        let elements = [
+            rect("d", 30.0, 28.0, 10.0, 10.0),
+            rect("a", 0.0, 0.0, 10.0, 10.0),
+            rect("c", 3.0, 30.0, 10.0, 10.0),
+            rect("b", 25.0, 2.0, 20.0, 10.0),
+        ];
+
+        // Gaps of 15 and 17 across and 16 down average out to 16. The second column is
+        // as wide as "b"
+        let tidy = engine.tidy_up(&elements, None);
+        let origins: Vec<(f64, f64)> = tidy.iter().map(|el| (el.bounding_box().x, el.bounding_box().y)).collect();
+        assert_eq!(origins, [(26.0, 26.0), (0.0, 0.0), (0.0, 26.0), (26.0, 0.0)]);
+
+        let tight = engine.tidy_up(&elements, Some(4.0));
+        let origins: Vec<(f64, f64)> = tight.iter().map(|el| (el.bounding_box().x, el.bounding_box().y)).collect();
+        assert_eq!(origins, [(14.0, 14.0), (0.0, 0.0), (0.0, 14.0), (14.0, 0.0)]);
+
+        assert!(engine.tidy_up(&[], None).is_empty());
+    }
+
+    #[test]
+    fn align_to_the_selection_a_key_element_or_a_frame() {
+        let engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 40.0, 20.0, 20.0, 30.0)];
+
+        let left = engine.align_elements(&elements, Alignment::Left, &AlignTarget::Selection).unwrap();
+        assert_eq!(left[0], elements[0]);
+        assert_eq!(left[1].bounding_box(), &BoundingBox::new(0.0, 20.0, 20.0, 30.0));
+
+        // The key element stays put and the rest line up with it
+        let key = AlignTarget::KeyElement { id: "b".to_string() };
+        let bottom = engine.align_elements(&elements, Alignment::Bottom, &key).unwrap();
+        assert_eq!(bottom[1], elements[1]);
+        assert_eq!(bottom[0].bounding_box(), &BoundingBox::new(0.0, 40.0, 10.0, 10.0));
+
+        let frame = AlignTarget::Frame { bounds: BoundingBox::new(100.0, 100.0, 200.0, 100.0) };
+        let centered = engine.align_elements(&elements, Alignment::Center, &frame).unwrap();
+        assert!(centered.iter().all(|el| close(el.bounding_box().center().x, 200.0)));
+
+        // A lone element can still be aligned to its frame, but not to itself
+        let middle = engine.align_elements(&elements[..1], Alignment::Middle, &frame).unwrap();
+        assert!(close(middle[0].bounding_box().center().y, 150.0));
+        let alone = engine.align_elements(&elements[..1], Alignment::Right, &AlignTarget::Selection).unwrap();
+        assert_eq!(alone, elements[..1]);
+    }
+
+    #[test]
+    fn align_rejects_unknown_keys_and_alignments() {
+        let engine = TransformEngine::new();
+        let key = AlignTarget::KeyElement { id: "missing".to_string() };
+        let result = engine.align_elements(&[rect("a", 0.0, 0.0, 10.0, 10.0)], Alignment::Left, &key);
+        assert_eq!(result, Err(TransformError::UnknownElement("missing".to_string())));
+
+        assert_eq!("middle".parse::<Alignment>(), Ok(Alignment::Middle));
+        assert!(matches!("diagonal".parse::<Alignment>(), Err(TransformError::InvalidParameters(_))));
+    }
+
+    #[test]
+    fn skew_drags_slide_the_dragged_edge() {
+        let mut engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 100.0, 50.0)];
+        let ids = vec!["a".to_string()];
+
+        // Dragging the top edge 25 right slants the box about its bottom edge
+        engine.start_skew(ids.clone(), HandleType::TopCenter, Point::new(50.0, 0.0), &document(&elements)).unwrap();
+        let update = engine.update_transform(Point::new(75.0, 0.0)).unwrap();
+        assert_eq!(update.elements[0].bounding_box(), &BoundingBox::new(0.0, 0.0, 125.0, 50.0));
+        let action = engine.finish_transform().unwrap();
+        assert_eq!(action.action_type, TransformActionType::Skew);
+        assert!(close(action.delta.skew_x, (-0.5f64).atan()) && action.delta.skew_y == 0.0);
+
+        // Dragging the right edge 20 down slants it vertically about the left edge
+        engine.start_skew(ids, HandleType::MiddleRight, Point::new(100.0, 25.0), &document(&elements)).unwrap();
+        let update = engine.update_transform(Point::new(100.0, 45.0)).unwrap();
+        let bounds = update.elements[0].bounding_box();
+        assert!(close(bounds.x, 0.0) && close(bounds.y, 0.0), "{:?}", bounds);
+        assert!(close(bounds.width, 100.0) && close(bounds.height, 70.0), "{:?}", bounds);
+        assert!(close(engine.finish_transform().unwrap().delta.skew_y, 0.2f64.atan()));
+    }
+
+    #[test]
+    fn skew_needs_an_edge_handle() {
+        let mut engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0)];
+        let result = engine.start_skew(vec!["a".to_string()], HandleType::TopLeft, Point::new(0.0, 0.0), &document(&elements));
+        assert!(matches!(result, Err(TransformError::InvalidParameters(_))));
+        assert!(engine.update_transform(Point::new(5.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn skew_element_keeps_the_origin_line_fixed() {
+        let engine = TransformEngine::new();
+        let element = rect("a", 0.0, 0.0, 10.0, 10.0);
+        let skewed = engine.skew_element(&element, FRAC_PI_2 / 2.0, 0.0, &Point::new(0.0, 10.0));
+        let bounds = skewed.bounding_box();
+        assert!(close(bounds.x, -10.0) && close(bounds.width, 20.0), "{:?}", bounds);
+        assert!(close(bounds.y, 0.0) && close(bounds.height, 10.0), "{:?}", bounds);
+        assert!(close(skewed.transform().skew_x, FRAC_PI_2 / 2.0));
+    }
+
+    fn assert_box(actual: &BoundingBox, expected: &BoundingBox) {
+        let pairs = [
+            (actual.x, expected.x),
+            (actual.y, expected.y),
+            (actual.width, expected.width),
+            (actual.height, expected.height),
+        ];
+        assert!(pairs.iter().all(|&(a, b)| close(a, b)), "{:?} != {:?}", actual, expected);
+    }
+
+    fn text(id: &str, x: f64, y: f64, width: f64, height: f64) -> VectorElement {
+        VectorElement::Text {
+            id: id.to_string(),
+            transform: Transform::translate(x, y),
+            style: Style { fill: None, stroke: None, shadow: None, opacity: None },
+            bounding_box: BoundingBox::new(x, y, width, height),
+            visible: true,
+            locked: false,
+            z_index: 0,
+            text: VectorText {
+                content: "Label".to_string(),
+                font_family: "Inter".to_string(),
+                font_size: 12.0,
+                font_weight: FontWeight::Normal,
+                font_style: FontStyle::Normal,
+                text_align: TextAlign::Left,
+                letter_spacing: 0.0,
+                line_height: 1.2,
+                path: None,
+            },
+            parent: None,
+            pivot: None,
+        }
+    }
+
+    #[test]
+    fn flipping_one_element_mirrors_it_in_place() {
+        let engine = TransformEngine::new();
+        let element = rect("a", 10.0, 0.0, 20.0, 10.0);
+
+        let flipped = engine.flip_horizontal(std::slice::from_ref(&element), false);
+        assert_box(flipped[0].bounding_box(), element.bounding_box());
+        // The local origin, the top-left corner, is now on the right
+        let origin = flipped[0].transform().transform_point(&Point::new(0.0, 0.0));
+        assert!(close(origin.x, 30.0) && close(origin.y, 0.0), "{:?}", origin);
+
+        let flipped = engine.flip_vertical(std::slice::from_ref(&element), false);
+        assert_box(flipped[0].bounding_box(), element.bounding_box());
+        let origin = flipped[0].transform().transform_point(&Point::new(0.0, 0.0));
+        assert!(close(origin.x, 10.0) && close(origin.y, 10.0), "{:?}", origin);
+    }
+
+    #[test]
+    fn flipping_a_selection_mirrors_it_about_its_center() {
+        let engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 30.0, 0.0, 10.0, 20.0)];
+
+        let flipped = engine.flip_horizontal(&elements, false);
+        assert_box(flipped[0].bounding_box(), &BoundingBox::new(30.0, 0.0, 10.0, 10.0));
+        assert_box(flipped[1].bounding_box(), &BoundingBox::new(0.0, 0.0, 10.0, 20.0));
+
+        let flipped = engine.flip_vertical(&elements, false);
+        assert_box(flipped[0].bounding_box(), &BoundingBox::new(0.0, 10.0, 10.0, 10.0));
+        assert_box(flipped[1].bounding_box(), &BoundingBox::new(30.0, 0.0, 10.0, 20.0));
+        assert!(engine.flip_horizontal(&[], false).is_empty());
+    }
+
+    #[test]
+    fn reflect_across_a_diagonal_swaps_the_axes() {
+        let engine = TransformEngine::new();
+        let elements = [rect("a", 10.0, 0.0, 20.0, 5.0)];
+        let reflected = engine.reflect_across(&elements, &Point::new(0.0, 0.0), FRAC_PI_2 / 2.0, false);
+        let bounds = reflected[0].bounding_box();
+        assert!(close(bounds.x, 0.0) && close(bounds.y, 10.0), "{:?}", bounds);
+        assert!(close(bounds.width, 5.0) && close(bounds.height, 20.0), "{:?}", bounds);
+    }
+
+    #[test]
+    fn readable_text_moves_and_turns_without_mirroring() {
+        let engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0), text("t", 30.0, 0.0, 40.0, 10.0)];
+
+        // Level text flipped sideways stays level and reads left to right
+        let flipped = engine.flip_horizontal(&elements, true);
+        assert_box(flipped[1].bounding_box(), &BoundingBox::new(0.0, 0.0, 40.0, 10.0));
+        let transform = flipped[1].transform();
+        assert!(close(transform.rotation, 0.0) && transform.scale_x > 0.0 && transform.scale_y > 0.0);
+
+        // Without the option the glyphs are mirrored too
+        let mirrored = engine.flip_horizontal(&elements, false);
+        assert_box(mirrored[1].bounding_box(), &BoundingBox::new(0.0, 0.0, 40.0, 10.0));
+        let transform = mirrored[1].transform();
+        assert!(transform.to_matrix().determinant() < 0.0, "{:?}", transform);
+
+        // Text tilted by 30° lines up with the rest of a selection reflected across a
+        // horizontal axis at -30°, still unmirrored
+        let tilted = engine.rotate_element(&elements[1], FRAC_PI_2 / 3.0, &elements[1].bounding_box().center());
+        let reflected = engine.reflect_across(std::slice::from_ref(&tilted), &Point::new(0.0, 0.0), 0.0, true);
+        let transform = reflected[0].transform();
+        assert!(close(transform.rotation, -FRAC_PI_2 / 3.0), "{:?}", transform);
+        assert!(transform.to_matrix().determinant() > 0.0);
+        let center = reflected[0].bounding_box().center();
+        let expected = tilted.bounding_box().center();
+        assert!(close(center.x, expected.x) && close(center.y, -expected.y), "{:?}", center);
+
+        // A steep line of text reflected across a vertical axis keeps reading downwards
+        let steep = engine.rotate_element(&elements[1], 80f64.to_radians(), &elements[1].bounding_box().center());
+        let reflected = engine.reflect_across(&[steep], &Point::new(0.0, 0.0), FRAC_PI_2, true);
+        assert!(close(reflected[0].transform().rotation, 100f64.to_radians()));
+    }
+
+    #[test]
+    fn a_set_pivot_follows_the_elements_and_drives_rotation() {
+        let mut engine = TransformEngine::new();
+        let elements = engine.set_pivot(&[rect("a", 0.0, 0.0, 40.0, 20.0)], Some(&Point::new(0.0, 0.0)));
+        assert_eq!(TransformEngine::selection_pivot(&elements), Some(Point::new(0.0, 0.0)));
+
+        // Stored in local space, so it moves with the element
+        let moved = engine.translate_element(&elements[0], &Point::new(5.0, 7.0));
+        assert_eq!(TransformEngine::selection_pivot(&[moved]), Some(Point::new(5.0, 7.0)));
+
+        // A quarter turn about the pivot instead of the center
+        let ids = vec!["a".to_string()];
+        engine.start_transform(ids, HandleType::Rotation, Point::new(10.0, 0.0), &document(&elements)).unwrap();
+        let update = engine.update_transform(Point::new(0.0, 10.0)).unwrap();
+        assert_box(update.elements[0].bounding_box(), &BoundingBox::new(-20.0, 0.0, 20.0, 40.0));
+        engine.finish_transform();
+
+        assert_eq!(TransformEngine::selection_pivot(&engine.set_pivot(&elements, None)), None);
+    }
+
+    #[test]
+    fn a_selection_only_shares_a_pivot_all_its_elements_agree_on() {
+        let engine = TransformEngine::new();
+        let pivot = Point::new(15.0, 5.0);
+        let both = engine.set_pivot(&[rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 20.0, 0.0, 10.0, 10.0)], Some(&pivot));
+        let shared = TransformEngine::selection_pivot(&both).unwrap();
+        assert!(close(shared.x, 15.0) && close(shared.y, 5.0));
+
+        let apart = [both[0].clone(), engine.set_pivot(&both[1..], Some(&Point::new(25.0, 5.0))).remove(0)];
+        assert_eq!(TransformEngine::selection_pivot(&apart), None);
+        let partly = [both[0].clone(), rect("c", 40.0, 0.0, 10.0, 10.0)];
+        assert_eq!(TransformEngine::selection_pivot(&partly), None);
+        assert_eq!(TransformEngine::selection_pivot(&[]), None);
+    }
+
+    #[test]
+    fn pivots_snap_to_the_oriented_selection_anchors() {
+        let engine = TransformEngine::new();
+        let element = rect("a", 100.0, 100.0, 40.0, 20.0);
+        let rotated = engine.rotate_element(&element, FRAC_PI_2, &Point::new(100.0, 100.0));
+
+        // The rotated box's far corner is at (80, 140); the canvas box's would be (100, 140)
+        let elements = [rotated];
+        let snapped = engine.snap_pivot(&elements, &Point::new(82.0, 138.0));
+        assert!(close(snapped.x, 80.0) && close(snapped.y, 140.0), "{:?}", snapped);
+        let free = Point::new(90.0, 130.0);
+        assert_eq!(engine.snap_pivot(&elements, &free), free);
+    }
+
+    #[test]
+    fn transform_values_report_each_reference_point() {
+        let elements = [rect("a", 10.0, 20.0, 40.0, 30.0)];
+        let expected = [
+            (ReferencePoint::TopLeft, 10.0, 20.0),
+            (ReferencePoint::TopCenter, 30.0, 20.0),
+            (ReferencePoint::TopRight, 50.0, 20.0),
+            (ReferencePoint::MiddleLeft, 10.0, 35.0),
+            (ReferencePoint::Center, 30.0, 35.0),
+            (ReferencePoint::MiddleRight, 50.0, 35.0),
+            (ReferencePoint::BottomLeft, 10.0, 50.0),
+            (ReferencePoint::BottomCenter, 30.0, 50.0),
+            (ReferencePoint::BottomRight, 50.0, 50.0),
+        ];
+        for (reference, x, y) in expected {
+            let values = TransformEngine::transform_values(&elements, reference).unwrap();
+            assert_eq!((values.x, values.y), (Some(x), Some(y)), "{:?}", reference);
+            assert_eq!((values.width, values.height, values.rotation), (Some(40.0), Some(30.0), Some(0.0)));
+        }
+
+        // Mixed rotations leave the rotation field blank
+        let engine = TransformEngine::new();
+        let mixed = [elements[0].clone(), engine.rotate_element(&elements[0], 0.3, &Point::new(0.0, 0.0))];
+        assert_eq!(TransformEngine::transform_values(&mixed, ReferencePoint::Center).unwrap().rotation, None);
+        assert_eq!(TransformEngine::transform_values(&[], ReferencePoint::Center), None);
+    }
+
+    #[test]
+    fn set_transform_values_keep_the_reference_point_fixed() {
+        let mut engine = TransformEngine::new();
+        let elements = [rect("a", 10.0, 20.0, 40.0, 30.0)];
+        let set = |engine: &TransformEngine, values: TransformValues, reference| {
+            engine.set_transform_values(&elements, &values, reference, TransformScope::Selection).unwrap()
+        };
+
+        // Position is where the reference point ends up
+        let moved = set(&engine, TransformValues { x: Some(100.0), y: Some(100.0), ..Default::default() }, ReferencePoint::Center);
+        assert_box(moved[0].bounding_box(), &BoundingBox::new(80.0, 85.0, 40.0, 30.0));
+
+        // Resizing about the bottom-right corner keeps that corner
+        let wider = set(&engine, TransformValues { width: Some(80.0), ..Default::default() }, ReferencePoint::BottomRight);
+        assert_box(wider[0].bounding_box(), &BoundingBox::new(-30.0, 20.0, 80.0, 30.0));
+
+        // Rotation turns about the reference point too
+        let turned = set(&engine, TransformValues { rotation: Some(FRAC_PI_2), ..Default::default() }, ReferencePoint::Center);
+        assert_box(turned[0].bounding_box(), &BoundingBox::new(15.0, 15.0, 30.0, 40.0));
+        assert!(close(turned[0].transform().rotation, FRAC_PI_2));
+
+        engine.set_constraint(ConstraintType::MaintainAspect, true);
+        let taller = set(&engine, TransformValues { height: Some(60.0), ..Default::default() }, ReferencePoint::TopLeft);
+        assert_box(taller[0].bounding_box(), &BoundingBox::new(10.0, 20.0, 80.0, 60.0));
+
+        let invalid = TransformValues { width: Some(0.0), ..Default::default() };
+        let result = engine.set_transform_values(&elements, &invalid, ReferencePoint::Center, TransformScope::Selection);
+        assert!(matches!(result, Err(TransformError::InvalidParameters(_))));
+    }
+
+    #[test]
+    fn set_transform_values_per_element_or_for_the_whole_selection() {
+        let engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 30.0, 0.0, 20.0, 10.0)];
+        let values = TransformValues { width: Some(100.0), ..Default::default() };
+
+        let together = engine.set_transform_values(&elements, &values, ReferencePoint::TopLeft, TransformScope::Selection).unwrap();
+        assert_box(together[0].bounding_box(), &BoundingBox::new(0.0, 0.0, 20.0, 10.0));
+        assert_box(together[1].bounding_box(), &BoundingBox::new(60.0, 0.0, 40.0, 10.0));
+
+        let each = engine.set_transform_values(&elements, &values, ReferencePoint::TopLeft, TransformScope::Each).unwrap();
+        assert_box(each[0].bounding_box(), &BoundingBox::new(0.0, 0.0, 100.0, 10.0));
+        assert_box(each[1].bounding_box(), &BoundingBox::new(30.0, 0.0, 100.0, 10.0));
+    }
+
+    /// A constraint with `params` given as the JSON object the JS side passes to
+    /// `set_constraint`.
+    fn constraint(constraint_type: ConstraintType, params: &str) -> Constraint {
+        Constraint { constraint_type, enabled: true, params: serde_json::from_str(params).unwrap() }
+    }
+
+    fn drag(engine: &mut TransformEngine, handle_type: HandleType, from: Point, to: Point, elements: &[VectorElement]) -> Transform {
+        engine.start_transform(vec![elements[0].id().to_string()], handle_type, from, &document(elements)).unwrap();
+        engine.update_transform(to).unwrap();
+        engine.finish_transform().unwrap().delta
+    }
+
+    #[test]
+    fn rotation_snaps_to_the_configured_increment() {
+        let mut engine = TransformEngine::new();
+        let elements = [rect("a", -10.0, -10.0, 20.0, 20.0)];
+        let to = Point::new(50f64.to_radians().cos() * 10.0, 50f64.to_radians().sin() * 10.0);
+
+        engine.set_constraint(ConstraintType::SnapRotation, true);
+        let delta = drag(&mut engine, HandleType::Rotation, Point::new(10.0, 0.0), to, &elements);
+        assert!(close(delta.rotation, 45f64.to_radians()));
+
+        engine.update_constraint(constraint(ConstraintType::SnapRotation, r#"{"increment": 90}"#));
+        let delta = drag(&mut engine, HandleType::Rotation, Point::new(10.0, 0.0), to, &elements);
+        assert!(close(delta.rotation, FRAC_PI_2));
+    }
+
+    #[test]
+    fn maintain_aspect_keeps_the_start_or_a_given_ratio() {
+        let mut engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 100.0, 100.0)];
+        let corner = Point::new(100.0, 100.0);
+
+        engine.set_constraint(ConstraintType::MaintainAspect, true);
+        let delta = drag(&mut engine, HandleType::BottomRight, corner, Point::new(300.0, 120.0), &elements);
+        assert!(close(delta.scale_x, 3.0) && close(delta.scale_y, 3.0));
+
+        engine.update_constraint(constraint(ConstraintType::MaintainAspect, r#"{"width": 16, "height": 9}"#));
+        let delta = drag(&mut engine, HandleType::BottomRight, corner, Point::new(160.0, 100.0), &elements);
+        assert!(close(delta.scale_x, 1.6) && close(delta.scale_y, 0.9));
+
+        engine.update_constraint(constraint(ConstraintType::MaintainAspect, r#"{"ratio": 2}"#));
+        let delta = drag(&mut engine, HandleType::BottomRight, corner, Point::new(100.0, 150.0), &elements);
+        assert!(close(delta.scale_x, 3.0) && close(delta.scale_y, 1.5));
+    }
+
+    #[test]
+    fn scale_limits_clamp_each_axis() {
+        let mut engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 100.0, 100.0)];
+        engine.update_constraint(constraint(ConstraintType::ScaleLimits, r#"{"min": 0.5, "max": 2}"#));
+
+        let delta = drag(&mut engine, HandleType::BottomRight, Point::new(100.0, 100.0), Point::new(300.0, 20.0), &elements);
+        assert!(close(delta.scale_x, 2.0) && close(delta.scale_y, 0.5));
+        let delta = drag(&mut engine, HandleType::BottomRight, Point::new(100.0, 100.0), Point::new(150.0, 120.0), &elements);
+        assert!(close(delta.scale_x, 1.5) && close(delta.scale_y, 1.2));
+    }
+
+    #[test]
+    fn scale_limits_keep_a_maintained_aspect_ratio() {
+        let mut engine = TransformEngine::new();
+        let elements = [rect("a", 0.0, 0.0, 100.0, 100.0)];
+        let corner = Point::new(100.0, 100.0);
+        engine.update_constraint(constraint(ConstraintType::ScaleLimits, r#"{"min": 0.5, "max": 2}"#));
+
+        // 2:1 would make this 300 x 150; only the width is over the limit, but both shrink
+        engine.update_constraint(constraint(ConstraintType::MaintainAspect, r#"{"ratio": 2}"#));
+        let delta = drag(&mut engine, HandleType::BottomRight, corner, Point::new(300.0, 100.0), &elements);
+        assert!(close(delta.scale_x, 2.0) && close(delta.scale_y, 1.0));
+
+        // Uniform scaling stops at the limit on both axes together
+        engine.update_constraint(constraint(ConstraintType::MaintainAspect, "null"));
+        let delta = drag(&mut engine, HandleType::BottomRight, corner, Point::new(20.0, 40.0), &elements);
+        assert!(close(delta.scale_x, 0.5) && close(delta.scale_y, 0.5));
+    }
+
+    #[test]
+    fn lock_axis_follows_its_param_or_the_larger_movement() {
+        let mut engine = TransformEngine::new();
+        engine.set_constraint(ConstraintType::SnapToObject, false);
+        let elements = [rect("a", 0.0, 0.0, 10.0, 10.0)];
+        let start = Point::new(5.0, 5.0);
+
+        engine.set_constraint(ConstraintType::LockAxis, true);
+        let delta = drag(&mut engine, HandleType::Center, start, Point::new(15.0, 8.0), &elements);
+        assert_eq!((delta.translate_x, delta.translate_y), (10.0, 0.0));
+
+        engine.update_constraint(constraint(ConstraintType::LockAxis, r#"{"axis": "y"}"#));
+        let delta = drag(&mut engine, HandleType::Center, start, Point::new(15.0, 8.0), &elements);
+        assert_eq!((delta.translate_x, delta.translate_y), (0.0, 3.0));
+
+        engine.update_constraint(constraint(ConstraintType::LockAxis, r#"{"axis": "x"}"#));
+        let delta = drag(&mut engine, HandleType::Center, start, Point::new(6.0, 25.0), &elements);
+        assert_eq!((delta.translate_x, delta.translate_y), (1.0, 0.0));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::core::vector::{BoundingBox, Point};
+use serde::{Deserialize, Serialize};
+
+/// A grid attached to a frame. Only enabled grids take part in snapping.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutGrid {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub kind: GridKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum GridKind {
+    /// Vertical bands across the frame's width.
+    Columns(GridTrack),
+    /// Horizontal bands down the frame's height.
+    Rows(GridTrack),
+    /// Horizontal lines every `height` for setting type, starting `offset` below the frame's top.
+    Baseline { height: f64, offset: f64 },
+    /// Two families of lines at `+angle` and `-angle` from horizontal (radians), crossing
+    /// the frame's top edge every `spacing`. An angle of 30° gives an isometric grid.
+    Angled { spacing: f64, angle: f64 },
+}
+
+/// Columns or rows. With `Stretch` the tracks fill the frame between the margins;
+/// otherwise they are `size` wide and pushed to the start, center or end, `margin`
+/// away from the edge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridTrack {
+    pub count: u32,
+    pub gutter: f64,
+    pub margin: f64,
+    pub alignment: GridAlignment,
+    pub size: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GridAlignment {
+    Stretch,
+    Start,
+    Center,
+    End,
+}
+
+/// The layout grids on one frame, along with the frame's bounds on the canvas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameGrids {
+    pub frame_id: String,
+    pub bounds: BoundingBox,
+    pub grids: Vec<LayoutGrid>,
+}
+
+impl GridTrack {
+    /// Track edges as offsets from the start of a frame `length` long.
+    pub fn edges(&self, length: f64) -> Vec<f64> {
+        if self.count == 0 {
+            return Vec::new();
+        }
+
+        let count = self.count as f64;
+        let gutters = self.gutter * (count - 1.0);
+        let stretched = (length - 2.0 * self.margin - gutters) / count;
+        let size = match (self.alignment, self.size) {
+            (GridAlignment::Stretch, _) | (_, None) => stretched,
+            (_, Some(size)) => size,
+        };
+        let total = size * count + gutters;
+        let start = match self.alignment {
+            GridAlignment::Stretch | GridAlignment::Start => self.margin,
+            GridAlignment::Center => (length - total) / 2.0,
+            GridAlignment::End => length - self.margin - total,
+        };
+
+        (0..self.count)
+            .flat_map(|i| {
+                let track_start = start + i as f64 * (size + self.gutter);
+                [track_start, track_start + size]
+            })
+            .collect()
+    }
+}
+
+impl LayoutGrid {
+    /// Vertical lines (x positions) and horizontal lines (y positions) this grid draws in `frame`.
+    pub fn lines(&self, frame: &BoundingBox) -> (Vec<f64>, Vec<f64>) {
+        match &self.kind {
+            GridKind::Columns(track) => (track.edges(frame.width).iter().map(|x| frame.x + x).collect(), Vec::new()),
+            GridKind::Rows(track) => (Vec::new(), track.edges(frame.height).iter().map(|y| frame.y + y).collect()),
+            GridKind::Baseline { height, offset } if *height > 0.0 => {
+                let count = ((frame.height - offset) / height).floor().max(-1.0) as i64;
+                let ys = (0..=count).map(|i| frame.y + offset + i as f64 * height).collect();
+                (Vec::new(), ys)
+            }
+            _ => (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// The line of this grid closest to `point` on each axis, as `(x, y)`. Baselines are
+    /// found arithmetically, so a tiny baseline height costs nothing.
+    pub fn nearest_lines(&self, frame: &BoundingBox, point: &Point) -> (Option<f64>, Option<f64>) {
+        let nearest = |edges: Vec<f64>, start: f64, value: f64| {
+            edges
+                .into_iter()
+                .map(|edge| start + edge)
+                .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+        };
+
+        match &self.kind {
+            GridKind::Columns(track) => (nearest(track.edges(frame.width), frame.x, point.x), None),
+            GridKind::Rows(track) => (None, nearest(track.edges(frame.height), frame.y, point.y)),
+            GridKind::Baseline { height, offset } if *height > 0.0 => {
+                let last = ((frame.height - offset) / height).floor();
+                if last < 0.0 {
+                    return (None, None);
+                }
+                let index = ((point.y - frame.y - offset) / height).round().clamp(0.0, last);
+                (None, Some(frame.y + offset + index * height))
+            }
+            _ => (None, None),
+        }
+    }
+
+    /// The closest crossing of an angled grid's lines, or `None` for other grids.
+    pub fn snap_to_lattice(&self, frame: &BoundingBox, point: &Point) -> Option<Point> {
+        let (spacing, angle) = match self.kind {
+            GridKind::Angled { spacing, angle } if spacing > 0.0 => (spacing, angle),
+            _ => return None,
+        };
+        let slope = angle.tan();
+        if !slope.is_finite() || slope.abs() < 1e-9 {
+            return None;
+        }
+
+        // Crossings sit at origin + a*u + b*v with u = (s/2, t*s/2), v = (s/2, -t*s/2)
+        let half = spacing / 2.0;
+        let x = point.x - frame.x;
+        let y = point.y - frame.y;
+        let a = (x / half + y / (slope * half)) / 2.0;
+        let b = (x / half - y / (slope * half)) / 2.0;
+
+        // The basis isn't orthogonal, so check all four surrounding crossings
+        [(a.floor(), b.floor()), (a.floor(), b.ceil()), (a.ceil(), b.floor()), (a.ceil(), b.ceil())]
+            .iter()
+            .map(|(a, b)| Point::new(frame.x + (a + b) * half, frame.y + (a - b) * slope * half))
+            .min_by(|p, q| p.distance_to(point).total_cmp(&q.distance_to(point)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(kind: GridKind) -> LayoutGrid {
+        LayoutGrid { enabled: true, kind }
+    }
+
+    #[test]
+    fn baselines_are_found_without_listing_them() {
+        let frame = BoundingBox::new(0.0, 100.0, 200.0, 300.0);
+        let baseline = grid(GridKind::Baseline { height: 8.0, offset: 4.0 });
+        assert_eq!(baseline.nearest_lines(&frame, &Point::new(0.0, 122.0)), (None, Some(120.0)));
+        // Before the first and past the last baseline, the end lines are nearest
+        assert_eq!(baseline.nearest_lines(&frame, &Point::new(0.0, 0.0)), (None, Some(104.0)));
+        assert_eq!(baseline.nearest_lines(&frame, &Point::new(0.0, 900.0)), (None, Some(400.0)));
+
+        let tiny = grid(GridKind::Baseline { height: 1e-12, offset: 0.0 });
+        let (_, y) = tiny.nearest_lines(&frame, &Point::new(0.0, 150.0));
+        assert!((y.unwrap() - 150.0).abs() < 1e-6);
+
+        let past_bottom = grid(GridKind::Baseline { height: 8.0, offset: 400.0 });
+        assert_eq!(past_bottom.nearest_lines(&frame, &Point::new(0.0, 150.0)), (None, None));
+    }
+
+    #[test]
+    fn nearest_column_edge() {
+        let frame = BoundingBox::new(100.0, 0.0, 400.0, 300.0);
+        let columns = grid(GridKind::Columns(GridTrack {
+            count: 4,
+            gutter: 20.0,
+            margin: 20.0,
+            alignment: GridAlignment::Stretch,
+            size: None,
+        }));
+        // 75 wide columns: edges at 120, 195, 215, 290, ...
+        assert_eq!(columns.nearest_lines(&frame, &Point::new(193.0, 50.0)), (Some(195.0), None));
+        assert_eq!(columns.nearest_lines(&frame, &Point::new(206.0, 50.0)), (Some(215.0), None));
+    }
+}
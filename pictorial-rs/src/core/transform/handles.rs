@@ -0,0 +1,161 @@
+use crate::core::vector::{BoundingBox, Point};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionHandle {
+    pub id: String,
+    pub handle_type: HandleType,
+    pub position: Point,
+    pub cursor: String,
+    /// Unrotated square around `position`; the drawn handle is this box turned by `rotation`.
+    pub bounds: BoundingBox,
+    /// Angle (radians) of the selection frame the handle belongs to.
+    pub rotation: f64,
+}
+
+impl SelectionHandle {
+    /// Hit-tests in the handle's own frame, so rotated handles don't pick up clicks
+    /// from the corners of their axis-aligned bounds.
+    pub fn contains_point(&self, point: &Point) -> bool {
+        let (sin, cos) = self.rotation.sin_cos();
+        let dx = point.x - self.position.x;
+        let dy = point.y - self.position.y;
+        let local_x = dx * cos + dy * sin;
+        let local_y = dy * cos - dx * sin;
+
+        local_x.abs() <= self.bounds.width / 2.0 && local_y.abs() <= self.bounds.height / 2.0
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HandleType {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    MiddleLeft,
+    MiddleRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+    Rotation,
+    Center,
+}
+
+impl HandleType {
+    /// Handles in hit-testing order: corners win over edges, edges over the center.
+    pub const ALL: [HandleType; 10] = [
+        HandleType::TopLeft,
+        HandleType::TopRight,
+        HandleType::BottomLeft,
+        HandleType::BottomRight,
+        HandleType::TopCenter,
+        HandleType::BottomCenter,
+        HandleType::MiddleLeft,
+        HandleType::MiddleRight,
+        HandleType::Rotation,
+        HandleType::Center,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            HandleType::TopLeft => "nw",
+            HandleType::TopCenter => "n",
+            HandleType::TopRight => "ne",
+            HandleType::MiddleLeft => "w",
+            HandleType::MiddleRight => "e",
+            HandleType::BottomLeft => "sw",
+            HandleType::BottomCenter => "s",
+            HandleType::BottomRight => "se",
+            HandleType::Rotation => "rotation",
+            HandleType::Center => "center",
+        }
+    }
+
+    pub fn cursor(&self) -> &'static str {
+        match self {
+            HandleType::TopLeft => "nw-resize",
+            HandleType::TopCenter => "n-resize",
+            HandleType::TopRight => "ne-resize",
+            HandleType::MiddleLeft => "w-resize",
+            HandleType::MiddleRight => "e-resize",
+            HandleType::BottomLeft => "sw-resize",
+            HandleType::BottomCenter => "s-resize",
+            HandleType::BottomRight => "se-resize",
+            HandleType::Rotation => "grab",
+            HandleType::Center => "move",
+        }
+    }
+
+    /// Resize cursor for a handle whose outward direction points at `angle` (radians,
+    /// y down). Rotation and move handles keep their fixed cursors.
+    pub fn cursor_at_angle(&self, angle: f64) -> &'static str {
+        const RESIZE_CURSORS: [&str; 8] = [
+            "e-resize", "se-resize", "s-resize", "sw-resize", "w-resize", "nw-resize", "n-resize", "ne-resize",
+        ];
+
+        if !self.is_corner() && !self.is_edge() {
+            return self.cursor();
+        }
+        let sector = (angle / std::f64::consts::FRAC_PI_4).round().rem_euclid(8.0) as usize;
+        RESIZE_CURSORS[sector % 8]
+    }
+
+    /// Outward direction of the handle in the selection's local frame, e.g. `(-1, -1)` for nw.
+    pub fn direction(&self) -> (f64, f64) {
+        let (fx, fy) = self.anchor();
+        (fx * 2.0 - 1.0, fy * 2.0 - 1.0)
+    }
+
+    pub fn is_corner(&self) -> bool {
+        matches!(
+            self,
+            HandleType::TopLeft | HandleType::TopRight | HandleType::BottomLeft | HandleType::BottomRight
+        )
+    }
+
+    pub fn is_edge(&self) -> bool {
+        matches!(
+            self,
+            HandleType::TopCenter | HandleType::BottomCenter | HandleType::MiddleLeft | HandleType::MiddleRight
+        )
+    }
+
+    /// Where the handle sits relative to the bounds, as fractions of width and height.
+    /// The rotation handle shares the top-center anchor and is offset above it.
+    pub fn anchor(&self) -> (f64, f64) {
+        match self {
+            HandleType::TopLeft => (0.0, 0.0),
+            HandleType::TopCenter | HandleType::Rotation => (0.5, 0.0),
+            HandleType::TopRight => (1.0, 0.0),
+            HandleType::MiddleLeft => (0.0, 0.5),
+            HandleType::MiddleRight => (1.0, 0.5),
+            HandleType::BottomLeft => (0.0, 1.0),
+            HandleType::BottomCenter => (0.5, 1.0),
+            HandleType::BottomRight => (1.0, 1.0),
+            HandleType::Center => (0.5, 0.5),
+        }
+    }
+
+    pub fn position(&self, bounds: &BoundingBox) -> Point {
+        let (fx, fy) = self.anchor();
+        Point::new(bounds.x + bounds.width * fx, bounds.y + bounds.height * fy)
+    }
+
+    /// The fixed point while dragging this handle: the opposite corner or edge for
+    /// scale handles, and the center for rotation and move.
+    pub fn origin(&self, bounds: &BoundingBox) -> Point {
+        match self {
+            HandleType::Rotation | HandleType::Center => bounds.center(),
+            _ => {
+                let (fx, fy) = self.anchor();
+                Point::new(
+                    bounds.x + bounds.width * (1.0 - fx),
+                    bounds.y + bounds.height * (1.0 - fy),
+                )
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 pub mod engine;
 pub mod constraint;
 pub mod handles;
+pub mod grid;
 
 pub use engine::*;
 pub use constraint::*;
-pub use handles::*; 
\ No newline at end of file
+pub use handles::*;
+pub use grid::*;
@@ -0,0 +1,472 @@
+use super::engine::*;
+use super::types::*;
+use serde::{Deserialize, Serialize};
+
+const EPSILON: f64 = 1e-9;
+
+/// How two filled areas combine, as in the Pathfinder panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BooleanOperation {
+    Union,
+    /// The first path with the second cut out of it.
+    Subtract,
+    Intersect,
+    /// Areas covered by exactly one of the paths.
+    Exclude,
+}
+
+impl BooleanOperation {
+    fn keeps(&self, in_first: bool, in_second: bool) -> bool {
+        match self {
+            BooleanOperation::Union => in_first || in_second,
+            BooleanOperation::Subtract => in_first && !in_second,
+            BooleanOperation::Intersect => in_first && in_second,
+            BooleanOperation::Exclude => in_first != in_second,
+        }
+    }
+}
+
+/// A crossing between curves `first` and `second` of the combined curve list, at
+/// parameters `t_first` and `t_second`.
+struct Crossing {
+    first: usize,
+    second: usize,
+    t_first: f64,
+    t_second: f64,
+    point: Point,
+}
+
+impl VectorPath {
+    pub fn union(&self, other: &VectorPath) -> VectorPath {
+        self.boolean(other, BooleanOperation::Union, 1e-3)
+    }
+
+    pub fn subtract(&self, other: &VectorPath) -> VectorPath {
+        self.boolean(other, BooleanOperation::Subtract, 1e-3)
+    }
+
+    pub fn intersect(&self, other: &VectorPath) -> VectorPath {
+        self.boolean(other, BooleanOperation::Intersect, 1e-3)
+    }
+
+    pub fn exclude(&self, other: &VectorPath) -> VectorPath {
+        self.boolean(other, BooleanOperation::Exclude, 1e-3)
+    }
+
+    /// Combines the filled areas of two paths, each read with its own fill rule.
+    ///
+    /// Both outlines are cut wherever they cross, and each piece is kept if the result is
+    /// filled on one side of it and empty on the other. Pieces stay the original lines
+    /// and curves (arcs come back as cubics), and every result boundary runs with its
+    /// fill on the same side, so holes come out as reversed subpaths under `NonZero`.
+    /// `tolerance` is how far apart two edges may be and still count as touching.
+    pub fn boolean(&self, other: &VectorPath, operation: BooleanOperation, tolerance: f64) -> VectorPath {
+        let tolerance = tolerance.max(1e-6);
+        let first = self.closed_subpaths();
+        let second = other.closed_subpaths();
+
+        let curves: Vec<BezierCurve> = first
+            .iter()
+            .chain(second.iter())
+            .flat_map(|s| s.curves.iter().cloned())
+            .collect();
+
+        let mut crossings = Vec::new();
+        for i in 0..curves.len() {
+            for j in i + 1..curves.len() {
+                find_crossings(&curves, i, j, tolerance, &mut crossings);
+            }
+        }
+
+        let fills = |point: &Point| {
+            let in_first = self.fill_rule.is_inside(winding_number(&first, point));
+            let in_second = other.fill_rule.is_inside(winding_number(&second, point));
+            operation.keeps(in_first, in_second)
+        };
+
+        let mut kept: Vec<BezierCurve> = Vec::new();
+        for (index, curve) in curves.iter().enumerate() {
+            for piece in split_at_crossings(curve, index, &crossings, tolerance) {
+                let middle = piece.point_at(0.5);
+                let normal = piece.normal_at(0.5).scale(tolerance);
+                let left = fills(&middle.add(&normal));
+                let right = fills(&middle.subtract(&normal));
+                if left == right {
+                    continue;
+                }
+
+                // Boundaries run with the filled side on the left of the normal
+                let piece = if left { piece } else { piece.reversed() };
+                let duplicate = kept.iter().any(|k| {
+                    k.start.distance_to(&piece.start) < tolerance
+                        && k.end.distance_to(&piece.end) < tolerance
+                        && k.point_at(0.5).distance_to(&middle) < tolerance
+                });
+                if !duplicate {
+                    kept.push(piece);
+                }
+            }
+        }
+
+        let mut result = VectorPath::new();
+        result.fill_color = self.fill_color.clone();
+        result.stroke_color = self.stroke_color.clone();
+        result.stroke_width = self.stroke_width;
+        result.opacity = self.opacity;
+        for ring in chain_rings(kept, tolerance) {
+            result.add_curves(&ring, true);
+        }
+        result.closed = !result.segments.is_empty();
+
+        result
+    }
+}
+
+fn hull(curve: &BezierCurve) -> (Point, Point) {
+    let points = [curve.start, curve.control1, curve.control2, curve.end];
+    let min = points.iter().fold(Point::new(f64::INFINITY, f64::INFINITY), |m, p| Point::new(m.x.min(p.x), m.y.min(p.y)));
+    let max = points.iter().fold(Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY), |m, p| Point::new(m.x.max(p.x), m.y.max(p.y)));
+    (min, max)
+}
+
+/// Furthest distance of the controls from the chord.
+fn flatness(curve: &BezierCurve) -> f64 {
+    let chord = curve.end.subtract(&curve.start);
+    let length = chord.x.hypot(chord.y);
+    let distance = |p: &Point| {
+        let v = p.subtract(&curve.start);
+        if length < EPSILON {
+            v.x.hypot(v.y)
+        } else {
+            (chord.x * v.y - chord.y * v.x).abs() / length
+        }
+    };
+    distance(&curve.control1).max(distance(&curve.control2))
+}
+
+fn find_crossings(curves: &[BezierCurve], i: usize, j: usize, tolerance: f64, crossings: &mut Vec<Crossing>) {
+    let mut found = Vec::new();
+    subdivide(&curves[i], (0.0, 1.0), &curves[j], (0.0, 1.0), tolerance, 0, &mut found);
+
+    for (t_first, t_second) in found {
+        let (t_first, t_second) = refine(&curves[i], &curves[j], t_first, t_second);
+        let point = curves[i].point_at(t_first).add(&curves[j].point_at(t_second)).scale(0.5);
+        let seen = crossings.iter().any(|c| c.first == i && c.second == j && c.point.distance_to(&point) < tolerance);
+        if !seen {
+            crossings.push(Crossing { first: i, second: j, t_first, t_second, point });
+        }
+    }
+}
+
+/// Halves whichever curve is still curved until both are flat enough to cross as lines.
+fn subdivide(
+    a: &BezierCurve,
+    a_range: (f64, f64),
+    b: &BezierCurve,
+    b_range: (f64, f64),
+    tolerance: f64,
+    depth: u32,
+    found: &mut Vec<(f64, f64)>,
+) {
+    let (a_min, a_max) = hull(a);
+    let (b_min, b_max) = hull(b);
+    if a_min.x > b_max.x + tolerance
+        || b_min.x > a_max.x + tolerance
+        || a_min.y > b_max.y + tolerance
+        || b_min.y > a_max.y + tolerance
+    {
+        return;
+    }
+
+    let a_flat = flatness(a) <= tolerance / 4.0;
+    let b_flat = flatness(b) <= tolerance / 4.0;
+    if (a_flat && b_flat) || depth >= 32 {
+        let map = |range: (f64, f64), s: f64| range.0 + s * (range.1 - range.0);
+        for (s, u) in line_crossings(a, b, tolerance) {
+            found.push((map(a_range, s), map(b_range, u)));
+        }
+        return;
+    }
+
+    let halves = |curve: &BezierCurve, range: (f64, f64), flat: bool| {
+        if flat {
+            vec![(curve.clone(), range)]
+        } else {
+            let (left, right) = curve.split(0.5);
+            let middle = (range.0 + range.1) / 2.0;
+            vec![(left, (range.0, middle)), (right, (middle, range.1))]
+        }
+    };
+    for (a_half, a_half_range) in halves(a, a_range, a_flat) {
+        for (b_half, b_half_range) in halves(b, b_range, b_flat) {
+            subdivide(&a_half, a_half_range, &b_half, b_half_range, tolerance, depth + 1, found);
+        }
+    }
+}
+
+/// Where the chords of two flat curves meet, as parameters along each. Overlapping
+/// collinear chords meet at the ends of the overlap.
+fn line_crossings(a: &BezierCurve, b: &BezierCurve, tolerance: f64) -> Vec<(f64, f64)> {
+    let da = a.end.subtract(&a.start);
+    let db = b.end.subtract(&b.start);
+    let offset = b.start.subtract(&a.start);
+    let denominator = da.x * db.y - da.y * db.x;
+    let (la, lb) = (da.x.hypot(da.y), db.x.hypot(db.y));
+    if la < EPSILON || lb < EPSILON {
+        return Vec::new();
+    }
+
+    if denominator.abs() > EPSILON * la * lb {
+        let s = (offset.x * db.y - offset.y * db.x) / denominator;
+        let u = (offset.x * da.y - offset.y * da.x) / denominator;
+        let slack_a = tolerance / la;
+        let slack_b = tolerance / lb;
+        if s >= -slack_a && s <= 1.0 + slack_a && u >= -slack_b && u <= 1.0 + slack_b {
+            return vec![(s.clamp(0.0, 1.0), u.clamp(0.0, 1.0))];
+        }
+        return Vec::new();
+    }
+
+    // Parallel: only collinear chords touch
+    if (da.x * offset.y - da.y * offset.x).abs() / la > tolerance {
+        return Vec::new();
+    }
+    let along_a = |p: &Point| {
+        let v = p.subtract(&a.start);
+        (v.x * da.x + v.y * da.y) / (la * la)
+    };
+    let along_b = |p: &Point| {
+        let v = p.subtract(&b.start);
+        (v.x * db.x + v.y * db.y) / (lb * lb)
+    };
+
+    let mut overlap = Vec::new();
+    for point in [b.start, b.end, a.start, a.end] {
+        let (s, u) = (along_a(&point), along_b(&point));
+        if (-EPSILON..=1.0 + EPSILON).contains(&s) && (-EPSILON..=1.0 + EPSILON).contains(&u) {
+            overlap.push((s.clamp(0.0, 1.0), u.clamp(0.0, 1.0)));
+        }
+    }
+    overlap
+}
+
+/// Newton steps on `a(s) = b(t)` from the flat-chord estimate, kept only if they help.
+fn refine(a: &BezierCurve, b: &BezierCurve, s: f64, t: f64) -> (f64, f64) {
+    let gap = |s: f64, t: f64| a.point_at(s).distance_to(&b.point_at(t));
+    let (mut rs, mut rt) = (s, t);
+    for _ in 0..8 {
+        let d = a.point_at(rs).subtract(&b.point_at(rt));
+        if d.x.hypot(d.y) < 1e-12 {
+            break;
+        }
+        let da = a.derivative_at(rs);
+        let db = b.derivative_at(rt);
+        let determinant = db.x * da.y - da.x * db.y;
+        if determinant.abs() < EPSILON {
+            break;
+        }
+        rs += (d.x * db.y - db.x * d.y) / determinant;
+        rt += (d.x * da.y - da.x * d.y) / determinant;
+    }
+
+    if (0.0..=1.0).contains(&rs) && (0.0..=1.0).contains(&rt) && gap(rs, rt) <= gap(s, t) {
+        (rs, rt)
+    } else {
+        (s, t)
+    }
+}
+
+/// Cuts a curve at its crossings. Each cut uses the shared crossing point so pieces
+/// from different curves meet exactly.
+fn split_at_crossings(curve: &BezierCurve, index: usize, crossings: &[Crossing], tolerance: f64) -> Vec<BezierCurve> {
+    let mut cuts: Vec<(f64, Point)> = crossings
+        .iter()
+        .filter_map(|c| {
+            if c.first == index {
+                Some((c.t_first, c.point))
+            } else if c.second == index {
+                Some((c.t_second, c.point))
+            } else {
+                None
+            }
+        })
+        .filter(|(t, point)| {
+            *t > EPSILON && *t < 1.0 - EPSILON && point.distance_to(&curve.start) > tolerance / 2.0 && point.distance_to(&curve.end) > tolerance / 2.0
+        })
+        .collect();
+    cuts.sort_by(|a, b| a.0.total_cmp(&b.0));
+    cuts.dedup_by(|a, b| a.1.distance_to(&b.1) < tolerance / 2.0);
+
+    let mut pieces = Vec::with_capacity(cuts.len() + 1);
+    let (mut t0, mut start) = (0.0, curve.start);
+    for (t, point) in cuts.into_iter().chain(std::iter::once((1.0, curve.end))) {
+        let mut piece = curve.subcurve(t0, t);
+        piece.start = start;
+        piece.end = point;
+        if piece.start.distance_to(&piece.end) > EPSILON || flatness(&piece) > EPSILON {
+            pieces.push(piece);
+        }
+        t0 = t;
+        start = point;
+    }
+    pieces
+}
+
+/// Joins boundary pieces end to start into closed rings.
+fn chain_rings(mut pieces: Vec<BezierCurve>, tolerance: f64) -> Vec<Vec<BezierCurve>> {
+    let mut rings = Vec::new();
+    while let Some(first) = pieces.pop() {
+        let start = first.start;
+        let mut ring = vec![first];
+        loop {
+            let end = ring[ring.len() - 1].end;
+            if end.distance_to(&start) < tolerance {
+                break;
+            }
+            match pieces.iter().position(|p| p.start.distance_to(&end) < tolerance) {
+                Some(next) => {
+                    let mut piece = pieces.swap_remove(next);
+                    piece.start = end;
+                    ring.push(piece);
+                }
+                None => break,
+            }
+        }
+
+        // A ring that can't be finished means the pieces were cut inconsistently. Closing
+        // it with a straight line would hide that behind a wrong shape, so drop it instead
+        if ring[ring.len() - 1].end.distance_to(&start) < tolerance {
+            rings.push(ring);
+        }
+    }
+    rings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> VectorPath {
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(x, y))
+            .line_to(Point::new(x + width, y))
+            .line_to(Point::new(x + width, y + height))
+            .line_to(Point::new(x, y + height))
+            .close();
+        path
+    }
+
+    fn circle(x: f64, y: f64, radius: f64) -> VectorPath {
+        let mut path = VectorPath::new();
+        path.arc_to(Point::new(x, y), radius, 0.0, std::f64::consts::TAU, true).close();
+        path
+    }
+
+    /// Filled area, counting reversed holes as negative.
+    fn area(path: &VectorPath) -> f64 {
+        let signed: f64 = path
+            .flatten(1e-3)
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .zip(ring.iter().cycle().skip(1))
+                    .map(|(a, b)| a.x * b.y - b.x * a.y)
+                    .sum::<f64>()
+                    / 2.0
+            })
+            .sum();
+        signed.abs()
+    }
+
+    fn rings(path: &VectorPath) -> usize {
+        path.segments.iter().filter(|segment| matches!(segment, PathSegment::Close)).count()
+    }
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn overlapping_squares() {
+        let a = rect(0.0, 0.0, 10.0, 10.0);
+        let b = rect(5.0, 5.0, 10.0, 10.0);
+        assert!(close(area(&a.union(&b)), 175.0, 1e-6));
+        assert!(close(area(&a.intersect(&b)), 25.0, 1e-6));
+        assert!(close(area(&a.subtract(&b)), 75.0, 1e-6));
+        assert!(close(area(&a.exclude(&b)), 150.0, 1e-6));
+        assert_eq!(rings(&a.union(&b)), 1);
+        assert_eq!(rings(&a.exclude(&b)), 2);
+    }
+
+    #[test]
+    fn cutting_a_hole() {
+        let outer = rect(0.0, 0.0, 10.0, 10.0);
+        let inner = rect(3.0, 3.0, 4.0, 4.0);
+        let ring = outer.subtract(&inner);
+        assert_eq!(rings(&ring), 2);
+        assert!(close(area(&ring), 84.0, 1e-6));
+        assert!(!ring.contains_point(&Point::new(5.0, 5.0)));
+        assert!(ring.contains_point(&Point::new(1.0, 5.0)));
+
+        // Nothing is left of the hole once it is cut out of itself
+        assert!(inner.subtract(&outer).segments.is_empty());
+    }
+
+    #[test]
+    fn squares_sharing_an_edge() {
+        let left = rect(0.0, 0.0, 10.0, 10.0);
+        let right = rect(10.0, 0.0, 10.0, 10.0);
+        let merged = left.union(&right);
+        assert_eq!(rings(&merged), 1);
+        assert!(close(area(&merged), 200.0, 1e-6));
+        assert!(left.intersect(&right).segments.is_empty());
+        assert!(close(area(&left.subtract(&right)), 100.0, 1e-6));
+    }
+
+    #[test]
+    fn even_odd_inputs() {
+        let mut framed = rect(0.0, 0.0, 10.0, 10.0);
+        framed.segments.extend(rect(2.0, 2.0, 6.0, 6.0).segments);
+        framed.fill_rule = FillRule::EvenOdd;
+        let other = rect(5.0, 5.0, 10.0, 10.0);
+
+        // The frame covers 64; it shares 25 - 9 = 16 with the other square
+        assert!(close(area(&framed.union(&other)), 148.0, 1e-6));
+        assert!(close(area(&framed.intersect(&other)), 16.0, 1e-6));
+
+        // Read as non-zero, the same outlines fill the whole square
+        framed.fill_rule = FillRule::NonZero;
+        assert!(close(area(&framed.intersect(&other)), 25.0, 1e-6));
+    }
+
+    #[test]
+    fn coincident_curves() {
+        let a = circle(10.0, 5.0, 6.0);
+        let full = std::f64::consts::PI * 36.0;
+        let union = a.union(&a.clone());
+        assert_eq!(rings(&union), 1);
+        assert!(close(area(&union), full, 0.05));
+        assert!(close(area(&a.intersect(&a.clone())), full, 0.05));
+        assert!(a.exclude(&a.clone()).segments.is_empty());
+
+        // Discs of radius r with centres d apart overlap in a lens of
+        // 2r²·acos(d/2r) - (d/2)·√(4r² - d²)
+        let b = circle(15.0, 5.0, 6.0);
+        let lens = 72.0 * (5.0f64 / 12.0).acos() - 2.5 * 119.0f64.sqrt();
+        assert!(close(area(&a.intersect(&b)), lens, 0.05));
+        assert!(close(area(&a.union(&b)), 2.0 * full - lens, 0.05));
+    }
+
+    #[test]
+    fn unfinished_rings_are_dropped() {
+        let (a, b, c) = (Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0));
+        let open = vec![BezierCurve::from_line(a, b), BezierCurve::from_line(b, c)];
+        assert!(chain_rings(open, 1e-3).is_empty());
+
+        let triangle = vec![BezierCurve::from_line(a, b), BezierCurve::from_line(b, c), BezierCurve::from_line(c, a)];
+        let rings = chain_rings(triangle, 1e-3);
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].len(), 3);
+    }
+}
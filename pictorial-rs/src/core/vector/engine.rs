@@ -0,0 +1,1404 @@
+use super::boolean::BooleanOperation;
+use super::hit_test::*;
+use super::pencil::*;
+use super::tessellation::Mesh;
+use super::types::*;
+use crate::math::Matrix3;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{FRAC_PI_2, TAU};
+use wasm_bindgen::prelude::*;
+
+const EPSILON: f64 = 1e-9;
+
+/// Miter joins longer than this many half stroke widths are drawn beveled, as in SVG.
+pub const MITER_LIMIT: f64 = 4.0;
+
+/// The closest point on a curve to some query point.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NearestPoint {
+    pub t: f64,
+    pub point: Point,
+    pub distance: f64,
+}
+
+/// One continuous run of a path as cubic curves. Lines and arcs are converted, and a
+/// closed subpath ends with a curve back to its start.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Subpath {
+    pub curves: Vec<BezierCurve>,
+    pub closed: bool,
+}
+
+/// Path and curve geometry for JS, mirroring the TypeScript `VectorEngine`.
+#[wasm_bindgen]
+pub struct VectorEngine {
+    tolerance: f64,
+}
+
+impl Default for VectorEngine {
+    fn default() -> Self {
+        VectorEngine::new()
+    }
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(value).map_err(JsValue::from)
+}
+
+#[wasm_bindgen]
+impl VectorEngine {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> VectorEngine {
+        VectorEngine { tolerance: 1e-3 }
+    }
+
+    /// How closely arc lengths and nearest points are approximated, in canvas units.
+    pub fn set_tolerance(&mut self, tolerance: f64) {
+        if tolerance > 0.0 {
+            self.tolerance = tolerance;
+        }
+    }
+
+    // BEZIER CURVE OPERATIONS
+
+    #[wasm_bindgen(js_name = get_bezier_point)]
+    pub fn get_bezier_point_js(&self, curve: JsValue, t: f64) -> Result<Point, JsValue> {
+        let curve: BezierCurve = serde_wasm_bindgen::from_value(curve)?;
+        Ok(curve.point_at(t))
+    }
+
+    #[wasm_bindgen(js_name = get_bezier_tangent)]
+    pub fn get_bezier_tangent_js(&self, curve: JsValue, t: f64) -> Result<Point, JsValue> {
+        let curve: BezierCurve = serde_wasm_bindgen::from_value(curve)?;
+        Ok(curve.tangent_at(t))
+    }
+
+    #[wasm_bindgen(js_name = get_bezier_normal)]
+    pub fn get_bezier_normal_js(&self, curve: JsValue, t: f64) -> Result<Point, JsValue> {
+        let curve: BezierCurve = serde_wasm_bindgen::from_value(curve)?;
+        Ok(curve.normal_at(t))
+    }
+
+    #[wasm_bindgen(js_name = split_bezier)]
+    pub fn split_bezier_js(&self, curve: JsValue, t: f64) -> Result<JsValue, JsValue> {
+        let curve: BezierCurve = serde_wasm_bindgen::from_value(curve)?;
+        to_js(&curve.split(t))
+    }
+
+    #[wasm_bindgen(js_name = get_bezier_length)]
+    pub fn get_bezier_length_js(&self, curve: JsValue) -> Result<f64, JsValue> {
+        let curve: BezierCurve = serde_wasm_bindgen::from_value(curve)?;
+        Ok(curve.length_with_tolerance(self.tolerance))
+    }
+
+    #[wasm_bindgen(js_name = nearest_point_on_bezier)]
+    pub fn nearest_point_on_bezier_js(&self, curve: JsValue, point: &Point) -> Result<JsValue, JsValue> {
+        let curve: BezierCurve = serde_wasm_bindgen::from_value(curve)?;
+        to_js(&curve.nearest_point(point))
+    }
+
+    // PATH OPERATIONS
+
+    #[wasm_bindgen(js_name = create_path)]
+    pub fn create_path_js(&self) -> Result<JsValue, JsValue> {
+        to_js(&VectorPath::new())
+    }
+
+    #[wasm_bindgen(js_name = move_to)]
+    pub fn move_to_js(&self, path: JsValue, point: &Point) -> Result<JsValue, JsValue> {
+        let mut path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        path.move_to(*point);
+        to_js(&path)
+    }
+
+    #[wasm_bindgen(js_name = line_to)]
+    pub fn line_to_js(&self, path: JsValue, point: &Point) -> Result<JsValue, JsValue> {
+        let mut path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        path.line_to(*point);
+        to_js(&path)
+    }
+
+    #[wasm_bindgen(js_name = curve_to)]
+    pub fn curve_to_js(&self, path: JsValue, control1: &Point, control2: &Point, end: &Point) -> Result<JsValue, JsValue> {
+        let mut path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        path.curve_to(*control1, *control2, *end);
+        to_js(&path)
+    }
+
+    #[wasm_bindgen(js_name = arc_to)]
+    pub fn arc_to_js(
+        &self,
+        path: JsValue,
+        center: &Point,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        clockwise: bool,
+    ) -> Result<JsValue, JsValue> {
+        let mut path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        path.arc_to(*center, radius, start_angle, end_angle, clockwise);
+        to_js(&path)
+    }
+
+    /// `stroke` may be omitted for the bounds of the geometry alone.
+    #[wasm_bindgen(js_name = get_path_bounds)]
+    pub fn get_path_bounds_js(&self, path: JsValue, stroke: JsValue) -> Result<JsValue, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        let stroke: Option<Stroke> = serde_wasm_bindgen::from_value(stroke)?;
+        match stroke {
+            Some(stroke) => to_js(&path.stroke_bounds(&stroke)),
+            None => to_js(&path.bounds()),
+        }
+    }
+
+    /// Canvas bounds of a path or shape element computed from its geometry, for
+    /// refreshing the cached `bounding_box`.
+    #[wasm_bindgen(js_name = get_element_bounds)]
+    pub fn get_element_bounds_js(&self, element: JsValue, include_stroke: bool) -> Result<JsValue, JsValue> {
+        let element: VectorElement = serde_wasm_bindgen::from_value(element)?;
+        to_js(&element_bounds(&element, include_stroke))
+    }
+
+    #[wasm_bindgen(js_name = close_path)]
+    pub fn close_path_js(&self, path: JsValue) -> Result<JsValue, JsValue> {
+        let mut path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        path.close();
+        to_js(&path)
+    }
+
+    // BOOLEAN OPERATIONS
+
+    /// Unlike the TypeScript version this returns a single path, since holes need to stay
+    /// with the outline around them.
+    #[wasm_bindgen(js_name = union)]
+    pub fn union_js(&self, path1: JsValue, path2: JsValue) -> Result<JsValue, JsValue> {
+        self.boolean(path1, path2, BooleanOperation::Union)
+    }
+
+    #[wasm_bindgen(js_name = subtract)]
+    pub fn subtract_js(&self, path1: JsValue, path2: JsValue) -> Result<JsValue, JsValue> {
+        self.boolean(path1, path2, BooleanOperation::Subtract)
+    }
+
+    #[wasm_bindgen(js_name = intersect)]
+    pub fn intersect_js(&self, path1: JsValue, path2: JsValue) -> Result<JsValue, JsValue> {
+        self.boolean(path1, path2, BooleanOperation::Intersect)
+    }
+
+    #[wasm_bindgen(js_name = exclude)]
+    pub fn exclude_js(&self, path1: JsValue, path2: JsValue) -> Result<JsValue, JsValue> {
+        self.boolean(path1, path2, BooleanOperation::Exclude)
+    }
+
+    /// Combines several paths in order, e.g. the whole selection in the Pathfinder panel.
+    #[wasm_bindgen(js_name = combine_paths)]
+    pub fn combine_paths_js(&self, paths: JsValue, operation: JsValue) -> Result<JsValue, JsValue> {
+        let paths: Vec<VectorPath> = serde_wasm_bindgen::from_value(paths)?;
+        let operation: BooleanOperation = serde_wasm_bindgen::from_value(operation)?;
+        let combined = paths
+            .split_first()
+            .map(|(first, rest)| rest.iter().fold(first.clone(), |acc, path| acc.boolean(path, operation, self.tolerance)));
+        to_js(&combined)
+    }
+
+    // OUTLINE AND OFFSET
+
+    /// "Outline Stroke": the painted area of the path's stroke as a filled path.
+    #[wasm_bindgen(js_name = outline_stroke)]
+    pub fn outline_stroke_js(&self, path: JsValue, stroke: JsValue) -> Result<JsValue, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        let stroke: Stroke = serde_wasm_bindgen::from_value(stroke)?;
+        to_js(&path.outline_stroke(&stroke, self.tolerance))
+    }
+
+    /// "Offset Path": positive distances grow the shape, negative ones shrink it.
+    #[wasm_bindgen(js_name = offset_path)]
+    pub fn offset_path_js(&self, path: JsValue, distance: f64, join: JsValue) -> Result<JsValue, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        let join: LineJoin = serde_wasm_bindgen::from_value(join)?;
+        to_js(&path.offset(distance, join, self.tolerance))
+    }
+
+    // SIMPLIFY AND SMOOTH
+
+    /// `tolerance` is how far the result may stray from the original, in canvas units.
+    #[wasm_bindgen(js_name = simplify_path)]
+    pub fn simplify_path_js(&self, path: JsValue, tolerance: f64) -> Result<JsValue, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        to_js(&path.simplify(tolerance))
+    }
+
+    /// Turns that exceed `corner_angle` (radians) stay sharp.
+    #[wasm_bindgen(js_name = smooth_path)]
+    pub fn smooth_path_js(&self, path: JsValue, corner_angle: f64, tolerance: f64) -> Result<JsValue, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        to_js(&path.smooth(corner_angle, tolerance))
+    }
+
+    // PENCIL
+
+    /// Fits the pencil tool's samples (`{ x, y, time, pressure? }`) with cubics.
+    /// `fidelity` runs from 0 (smoothest) to 1 (closest to the hand).
+    #[wasm_bindgen(js_name = fit_pencil_stroke)]
+    pub fn fit_pencil_stroke_js(&self, samples: JsValue, fidelity: f64) -> Result<JsValue, JsValue> {
+        let samples: Vec<PencilSample> = serde_wasm_bindgen::from_value(samples)?;
+        to_js(&fit_pencil(&samples, fidelity))
+    }
+
+    /// A filled outline whose width follows the samples' pressure, up to `width`.
+    #[wasm_bindgen(js_name = pencil_outline)]
+    pub fn pencil_outline_js(&self, samples: JsValue, fidelity: f64, width: f64) -> Result<JsValue, JsValue> {
+        let samples: Vec<PencilSample> = serde_wasm_bindgen::from_value(samples)?;
+        to_js(&pencil_outline(&samples, fidelity, width))
+    }
+
+    // TESSELLATION
+
+    /// Polylines for each subpath; `tolerance` should shrink as the zoom grows.
+    #[wasm_bindgen(js_name = flatten_path)]
+    pub fn flatten_path_js(&self, path: JsValue, tolerance: f64) -> Result<JsValue, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        to_js(&path.flatten(tolerance))
+    }
+
+    #[wasm_bindgen(js_name = tessellate_fill)]
+    pub fn tessellate_fill_js(&self, path: JsValue, tolerance: f64) -> Result<Mesh, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        path.tessellate_fill(tolerance).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = tessellate_stroke)]
+    pub fn tessellate_stroke_js(&self, path: JsValue, stroke: JsValue, tolerance: f64) -> Result<Mesh, JsValue> {
+        let path: VectorPath = serde_wasm_bindgen::from_value(path)?;
+        let stroke: Stroke = serde_wasm_bindgen::from_value(stroke)?;
+        path.tessellate_stroke(&stroke, tolerance).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = tessellate_shape_fill)]
+    pub fn tessellate_shape_fill_js(&self, shape: JsValue, tolerance: f64) -> Result<Mesh, JsValue> {
+        let shape: VectorShape = serde_wasm_bindgen::from_value(shape)?;
+        shape.tessellate_fill(tolerance).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = tessellate_shape_stroke)]
+    pub fn tessellate_shape_stroke_js(&self, shape: JsValue, stroke: JsValue, tolerance: f64) -> Result<Mesh, JsValue> {
+        let shape: VectorShape = serde_wasm_bindgen::from_value(shape)?;
+        let stroke: Stroke = serde_wasm_bindgen::from_value(stroke)?;
+        shape.tessellate_stroke(&stroke, tolerance).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    // HIT TESTING
+
+    /// `"fill"`, `"stroke"` or `"text"` for the part of the element under `point`, or
+    /// `null` when it misses. `tolerance` is in canvas units.
+    #[wasm_bindgen(js_name = hit_test)]
+    pub fn hit_test_js(&self, element: JsValue, point: &Point, tolerance: f64) -> Result<JsValue, JsValue> {
+        let element: VectorElement = serde_wasm_bindgen::from_value(element)?;
+        to_js(&hit_test(&element, point, tolerance))
+    }
+
+    /// Id of the topmost visible, unlocked element under `point`.
+    #[wasm_bindgen(js_name = element_at_point)]
+    pub fn element_at_point_js(&self, elements: JsValue, point: &Point, tolerance: f64) -> Result<Option<String>, JsValue> {
+        let elements: Vec<VectorElement> = serde_wasm_bindgen::from_value(elements)?;
+        Ok(element_at_point(&elements, point, tolerance).map(|element| element.id().to_string()))
+    }
+}
+
+impl VectorEngine {
+    fn boolean(&self, path1: JsValue, path2: JsValue, operation: BooleanOperation) -> Result<JsValue, JsValue> {
+        let path1: VectorPath = serde_wasm_bindgen::from_value(path1)?;
+        let path2: VectorPath = serde_wasm_bindgen::from_value(path2)?;
+        to_js(&path1.boolean(&path2, operation, self.tolerance))
+    }
+}
+
+impl BezierCurve {
+    pub fn new(start: Point, control1: Point, control2: Point, end: Point) -> BezierCurve {
+        BezierCurve { start, control1, control2, end }
+    }
+
+    /// A straight line as a cubic, with the controls a third of the way along.
+    pub fn from_line(start: Point, end: Point) -> BezierCurve {
+        let step = end.subtract(&start).scale(1.0 / 3.0);
+        BezierCurve::new(start, start.add(&step), end.subtract(&step), end)
+    }
+
+    pub fn point_at(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let a = mt * mt * mt;
+        let b = 3.0 * mt * mt * t;
+        let c = 3.0 * mt * t * t;
+        let d = t * t * t;
+
+        Point::new(
+            a * self.start.x + b * self.control1.x + c * self.control2.x + d * self.end.x,
+            a * self.start.y + b * self.control1.y + c * self.control2.y + d * self.end.y,
+        )
+    }
+
+    /// First derivative with respect to `t`; its length is the speed along the curve.
+    pub fn derivative_at(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let a = 3.0 * mt * mt;
+        let b = 6.0 * mt * t;
+        let c = 3.0 * t * t;
+
+        Point::new(
+            a * (self.control1.x - self.start.x) + b * (self.control2.x - self.control1.x) + c * (self.end.x - self.control2.x),
+            a * (self.control1.y - self.start.y) + b * (self.control2.y - self.control1.y) + c * (self.end.y - self.control2.y),
+        )
+    }
+
+    pub fn second_derivative_at(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        let p = |start: f64, control1: f64, control2: f64, end: f64| {
+            6.0 * mt * (control2 - 2.0 * control1 + start) + 6.0 * t * (end - 2.0 * control2 + control1)
+        };
+
+        Point::new(
+            p(self.start.x, self.control1.x, self.control2.x, self.end.x),
+            p(self.start.y, self.control1.y, self.control2.y, self.end.y),
+        )
+    }
+
+    /// Unit direction of travel at `t`. Where a control point sits on its end point
+    /// the derivative vanishes, so this falls back to the next control point along.
+    pub fn tangent_at(&self, t: f64) -> Point {
+        let derivative = self.derivative_at(t);
+        let length = derivative.x.hypot(derivative.y);
+        if length > EPSILON {
+            return derivative.scale(1.0 / length);
+        }
+
+        let (from, to) = if t < 0.5 {
+            let next = [self.control1, self.control2, self.end]
+                .into_iter()
+                .find(|p| p.distance_to(&self.start) > EPSILON)
+                .unwrap_or(self.end);
+            (self.start, next)
+        } else {
+            let previous = [self.control2, self.control1, self.start]
+                .into_iter()
+                .find(|p| p.distance_to(&self.end) > EPSILON)
+                .unwrap_or(self.start);
+            (previous, self.end)
+        };
+        let direction = to.subtract(&from);
+        let length = direction.x.hypot(direction.y);
+        if length > EPSILON {
+            direction.scale(1.0 / length)
+        } else {
+            Point::new(1.0, 0.0)
+        }
+    }
+
+    /// Unit normal at `t`: the tangent turned a quarter clockwise on screen (y down).
+    pub fn normal_at(&self, t: f64) -> Point {
+        let tangent = self.tangent_at(t);
+        Point::new(-tangent.y, tangent.x)
+    }
+
+    /// Splits at `t` with de Casteljau's algorithm.
+    pub fn split(&self, t: f64) -> (BezierCurve, BezierCurve) {
+        let lerp = |a: &Point, b: &Point| Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+
+        let p01 = lerp(&self.start, &self.control1);
+        let p12 = lerp(&self.control1, &self.control2);
+        let p23 = lerp(&self.control2, &self.end);
+        let p012 = lerp(&p01, &p12);
+        let p123 = lerp(&p12, &p23);
+        let mid = lerp(&p012, &p123);
+
+        (
+            BezierCurve::new(self.start, p01, p012, mid),
+            BezierCurve::new(mid, p123, p23, self.end),
+        )
+    }
+
+    /// The part of the curve between `t0` and `t1`.
+    pub fn subcurve(&self, t0: f64, t1: f64) -> BezierCurve {
+        let (_, right) = self.split(t0);
+        if t0 >= 1.0 - EPSILON {
+            return right;
+        }
+        let (middle, _) = right.split((t1 - t0) / (1.0 - t0));
+        middle
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length_with_tolerance(1e-3)
+    }
+
+    /// Arc length, subdividing until the control polygon and the chord agree to within
+    /// `tolerance`.
+    pub fn length_with_tolerance(&self, tolerance: f64) -> f64 {
+        fn measure(curve: &BezierCurve, tolerance: f64, depth: u32) -> f64 {
+            let chord = curve.start.distance_to(&curve.end);
+            let polygon = curve.start.distance_to(&curve.control1)
+                + curve.control1.distance_to(&curve.control2)
+                + curve.control2.distance_to(&curve.end);
+
+            if polygon - chord <= tolerance || depth >= 16 {
+                return (chord + polygon) / 2.0;
+            }
+            let (left, right) = curve.split(0.5);
+            measure(&left, tolerance / 2.0, depth + 1) + measure(&right, tolerance / 2.0, depth + 1)
+        }
+
+        measure(self, tolerance.max(EPSILON), 0)
+    }
+
+    /// Closest point on the curve to `point`: a coarse scan followed by Newton steps on
+    /// `(B(t) - point) · B'(t) = 0`.
+    pub fn nearest_point(&self, point: &Point) -> NearestPoint {
+        const SAMPLES: usize = 16;
+
+        let distance_at = |t: f64| self.point_at(t).distance_to(point);
+        let mut best_t = (0..=SAMPLES)
+            .map(|i| i as f64 / SAMPLES as f64)
+            .min_by(|a, b| distance_at(*a).total_cmp(&distance_at(*b)))
+            .unwrap_or(0.0);
+
+        for _ in 0..8 {
+            let offset = self.point_at(best_t).subtract(point);
+            let first = self.derivative_at(best_t);
+            let second = self.second_derivative_at(best_t);
+            let numerator = offset.x * first.x + offset.y * first.y;
+            let denominator = first.x * first.x + first.y * first.y + offset.x * second.x + offset.y * second.y;
+            if denominator.abs() < EPSILON {
+                break;
+            }
+
+            let next = (best_t - numerator / denominator).clamp(0.0, 1.0);
+            if (next - best_t).abs() < EPSILON {
+                best_t = next;
+                break;
+            }
+            // Newton can overshoot on flat stretches; only accept improvements
+            if distance_at(next) > distance_at(best_t) {
+                break;
+            }
+            best_t = next;
+        }
+
+        let nearest = self.point_at(best_t);
+        NearestPoint {
+            t: best_t,
+            point: nearest,
+            distance: nearest.distance_to(point),
+        }
+    }
+}
+
+/// Signed sweep of an arc in radians. Clockwise (on screen, y down) arcs run from
+/// `start_angle` towards increasing angles, as with canvas `arc`; a sweep of a full
+/// turn or more draws a full circle.
+pub fn arc_sweep(start_angle: f64, end_angle: f64, clockwise: bool) -> f64 {
+    let diff = end_angle - start_angle;
+    if clockwise {
+        if diff >= TAU { TAU } else { diff.rem_euclid(TAU) }
+    } else if -diff >= TAU {
+        -TAU
+    } else {
+        -(-diff).rem_euclid(TAU)
+    }
+}
+
+impl VectorPath {
+    /// Where the next segment starts: the end of the last one, or the start of the
+    /// current subpath after a close.
+    pub fn current_point(&self) -> Option<Point> {
+        let mut subpath_start = None;
+        let mut current = None;
+
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Move { point } => {
+                    subpath_start = Some(*point);
+                    current = Some(*point);
+                }
+                PathSegment::Line { point } => current = Some(*point),
+                PathSegment::Curve { curve } => current = Some(curve.end),
+                PathSegment::Arc { center, radius, start_angle, end_angle, clockwise } => {
+                    let end = start_angle + arc_sweep(*start_angle, *end_angle, *clockwise);
+                    current = Some(Point::new(center.x + radius * end.cos(), center.y + radius * end.sin()));
+                }
+                PathSegment::Close => current = subpath_start,
+            }
+        }
+
+        current
+    }
+
+    pub fn move_to(&mut self, point: Point) -> &mut Self {
+        self.segments.push(PathSegment::Move { point });
+        self
+    }
+
+    pub fn line_to(&mut self, point: Point) -> &mut Self {
+        self.segments.push(PathSegment::Line { point });
+        self
+    }
+
+    /// A cubic from the current point (or from `end` on an empty path).
+    pub fn curve_to(&mut self, control1: Point, control2: Point, end: Point) -> &mut Self {
+        let start = self.current_point().unwrap_or(end);
+        self.segments.push(PathSegment::Curve {
+            curve: BezierCurve::new(start, control1, control2, end),
+        });
+        self
+    }
+
+    /// A circular arc. Like canvas `arc`, a straight line joins the current point to the
+    /// arc's start; on an empty path the arc starts a new subpath.
+    pub fn arc_to(&mut self, center: Point, radius: f64, start_angle: f64, end_angle: f64, clockwise: bool) -> &mut Self {
+        if self.current_point().is_none() {
+            self.move_to(Point::new(
+                center.x + radius * start_angle.cos(),
+                center.y + radius * start_angle.sin(),
+            ));
+        }
+        self.segments.push(PathSegment::Arc {
+            center,
+            radius,
+            start_angle,
+            end_angle,
+            clockwise,
+        });
+        self
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        self.closed = true;
+        self.segments.push(PathSegment::Close);
+        self
+    }
+}
+
+/// Running min/max over points, for building bounding boxes.
+#[derive(Debug, Clone, Copy)]
+struct Extents {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+impl Extents {
+    fn new() -> Extents {
+        Extents {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+        }
+    }
+
+    fn include(&mut self, point: &Point) {
+        self.min_x = self.min_x.min(point.x);
+        self.min_y = self.min_y.min(point.y);
+        self.max_x = self.max_x.max(point.x);
+        self.max_y = self.max_y.max(point.y);
+    }
+
+    /// Includes the square around `point`, which contains the circle of `radius`.
+    fn include_circle(&mut self, point: &Point, radius: f64) {
+        self.include(&Point::new(point.x - radius, point.y - radius));
+        self.include(&Point::new(point.x + radius, point.y + radius));
+    }
+
+    fn to_bounds(self) -> Option<BoundingBox> {
+        (self.min_x <= self.max_x && self.min_y <= self.max_y)
+            .then(|| BoundingBox::new(self.min_x, self.min_y, self.max_x - self.min_x, self.max_y - self.min_y))
+    }
+}
+
+/// Roots of `a*t^2 + b*t + c` strictly inside (0, 1).
+fn quadratic_roots_in_unit(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let mut roots = Vec::with_capacity(2);
+    if a.abs() < EPSILON {
+        if b.abs() > EPSILON {
+            roots.push(-c / b);
+        }
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant >= 0.0 {
+            let sqrt = discriminant.sqrt();
+            roots.push((-b + sqrt) / (2.0 * a));
+            roots.push((-b - sqrt) / (2.0 * a));
+        }
+    }
+    roots.retain(|t| *t > 0.0 && *t < 1.0);
+    roots
+}
+
+/// Real roots of `a*t^3 + b*t^2 + c*t + d`, falling back to lower degrees when the
+/// leading coefficients vanish.
+fn polynomial_roots(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let scale = a.abs().max(b.abs()).max(c.abs()).max(d.abs());
+    if scale < EPSILON {
+        return Vec::new();
+    }
+    let (a, b, c, d) = (a / scale, b / scale, c / scale, d / scale);
+
+    if a.abs() < EPSILON {
+        if b.abs() < EPSILON {
+            return if c.abs() < EPSILON { Vec::new() } else { vec![-d / c] };
+        }
+        let discriminant = c * c - 4.0 * b * d;
+        if discriminant < 0.0 {
+            return Vec::new();
+        }
+        let sqrt = discriminant.sqrt();
+        return vec![(-c + sqrt) / (2.0 * b), (-c - sqrt) / (2.0 * b)];
+    }
+
+    // Depressed cubic x^3 + p*x + q with t = x - b/3
+    let (b, c, d) = (b / a, c / a, d / a);
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    let mut roots = if discriminant > EPSILON {
+        let sqrt = discriminant.sqrt();
+        vec![(-q / 2.0 + sqrt).cbrt() + (-q / 2.0 - sqrt).cbrt() - shift]
+    } else if discriminant < -EPSILON {
+        let r = (-p / 3.0).sqrt();
+        let phi = (-q / (2.0 * r * r * r)).clamp(-1.0, 1.0).acos();
+        (0..3)
+            .map(|k| 2.0 * r * ((phi - TAU * k as f64) / 3.0).cos() - shift)
+            .collect()
+    } else {
+        let u = (-q / 2.0).cbrt();
+        vec![2.0 * u - shift, -u - shift]
+    };
+
+    // One Newton step tidies up the precision lost in the closed form
+    for t in &mut roots {
+        let value = ((*t + b) * *t + c) * *t + d;
+        let slope = (3.0 * *t + 2.0 * b) * *t + c;
+        if slope.abs() > EPSILON {
+            *t -= value / slope;
+        }
+    }
+    roots
+}
+
+/// Signed crossings of the ray from `point` towards +x with `curve`, counting each
+/// curve from its start up to but not including its end.
+fn ray_crossings(curve: &BezierCurve, point: &Point) -> i32 {
+    let ys = [curve.start.y, curve.control1.y, curve.control2.y, curve.end.y];
+    let xs = [curve.start.x, curve.control1.x, curve.control2.x, curve.end.x];
+    if ys.iter().all(|y| *y < point.y) || ys.iter().all(|y| *y > point.y) || xs.iter().all(|x| *x < point.x) {
+        return 0;
+    }
+
+    let [p0, p1, p2, p3] = ys;
+    let roots = polynomial_roots(
+        -p0 + 3.0 * p1 - 3.0 * p2 + p3,
+        3.0 * p0 - 6.0 * p1 + 3.0 * p2,
+        3.0 * (p1 - p0),
+        p0 - point.y,
+    );
+
+    let mut crossings = 0;
+    let mut seen: Vec<f64> = Vec::with_capacity(3);
+    for t in roots {
+        if !(0.0..1.0).contains(&t) || seen.iter().any(|s| (s - t).abs() < EPSILON) {
+            continue;
+        }
+        seen.push(t);
+        let slope = curve.derivative_at(t).y;
+        if slope.abs() > EPSILON && curve.point_at(t).x > point.x {
+            crossings += if slope > 0.0 { 1 } else { -1 };
+        }
+    }
+    crossings
+}
+
+/// How many times `subpaths` wind around `point`. Open subpaths count as closed, the way
+/// they are filled.
+pub fn winding_number(subpaths: &[Subpath], point: &Point) -> i32 {
+    let closing = |subpath: &Subpath| {
+        let (first, last) = (subpath.curves.first()?, subpath.curves.last()?);
+        (first.start.distance_to(&last.end) > EPSILON).then(|| BezierCurve::from_line(last.end, first.start))
+    };
+    let closings: Vec<BezierCurve> = subpaths.iter().filter_map(closing).collect();
+    let curves = || subpaths.iter().flat_map(|s| s.curves.iter()).chain(closings.iter());
+
+    // A ray through a vertex can count it twice or not at all, so move it off any
+    // vertex by far less than the distances we care about
+    let mut probe = *point;
+    for attempt in 1..=8 {
+        if !curves().any(|c| (c.start.y - probe.y).abs() < 1e-9 || (c.end.y - probe.y).abs() < 1e-9) {
+            break;
+        }
+        probe.y = point.y + 1e-7 * attempt as f64;
+    }
+
+    curves().map(|curve| ray_crossings(curve, &probe)).sum()
+}
+
+/// Cubic pieces of at most a quarter turn approximating a circular arc.
+pub fn arc_to_curves(center: &Point, radius: f64, start_angle: f64, sweep: f64) -> Vec<BezierCurve> {
+    if sweep.abs() < EPSILON || radius <= 0.0 {
+        return Vec::new();
+    }
+
+    let count = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = sweep / count as f64;
+    let k = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+    let at = |angle: f64| Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin());
+
+    (0..count)
+        .map(|i| {
+            let a0 = start_angle + step * i as f64;
+            let a1 = a0 + step;
+            let (p0, p3) = (at(a0), at(a1));
+            BezierCurve::new(
+                p0,
+                Point::new(p0.x - k * a0.sin(), p0.y + k * a0.cos()),
+                Point::new(p3.x + k * a1.sin(), p3.y - k * a1.cos()),
+                p3,
+            )
+        })
+        .collect()
+}
+
+/// Exact bounds of a circular arc: its end points plus any quarter-turn points it passes.
+fn include_arc(extents: &mut Extents, center: &Point, radius: f64, start_angle: f64, sweep: f64) {
+    let at = |angle: f64| Point::new(center.x + radius * angle.cos(), center.y + radius * angle.sin());
+    let (from, to) = if sweep >= 0.0 {
+        (start_angle, start_angle + sweep)
+    } else {
+        (start_angle + sweep, start_angle)
+    };
+
+    extents.include(&at(from));
+    extents.include(&at(to));
+    let mut quarter = (from / FRAC_PI_2).ceil() * FRAC_PI_2;
+    while quarter < to {
+        extents.include(&at(quarter));
+        quarter += FRAC_PI_2;
+    }
+}
+
+impl BezierCurve {
+    /// Parameters in (0, 1) where the curve turns back in x or y.
+    pub fn extrema(&self) -> Vec<f64> {
+        let axis = |p0: f64, p1: f64, p2: f64, p3: f64| {
+            quadratic_roots_in_unit(
+                3.0 * (-p0 + 3.0 * p1 - 3.0 * p2 + p3),
+                6.0 * (p0 - 2.0 * p1 + p2),
+                3.0 * (p1 - p0),
+            )
+        };
+
+        let mut ts = axis(self.start.x, self.control1.x, self.control2.x, self.end.x);
+        ts.extend(axis(self.start.y, self.control1.y, self.control2.y, self.end.y));
+        ts.sort_by(|a, b| a.total_cmp(b));
+        ts.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+        ts
+    }
+
+    /// Tight bounds from the end points and the extrema, not the control points.
+    pub fn bounds(&self) -> BoundingBox {
+        let mut extents = Extents::new();
+        extents.include(&self.start);
+        extents.include(&self.end);
+        for t in self.extrema() {
+            extents.include(&self.point_at(t));
+        }
+        extents.to_bounds().unwrap_or_else(|| BoundingBox::new(self.start.x, self.start.y, 0.0, 0.0))
+    }
+
+    /// Whether the controls lie on the chord, so the curve draws a straight line.
+    pub fn is_line(&self) -> bool {
+        let chord = self.end.subtract(&self.start);
+        let length = chord.x.hypot(chord.y);
+        let off_chord = |p: &Point| {
+            let v = p.subtract(&self.start);
+            if length < EPSILON {
+                v.x.hypot(v.y)
+            } else {
+                (chord.x * v.y - chord.y * v.x).abs() / length
+            }
+        };
+        off_chord(&self.control1) < 1e-6 && off_chord(&self.control2) < 1e-6
+    }
+
+    pub fn reversed(&self) -> BezierCurve {
+        BezierCurve::new(self.end, self.control2, self.control1, self.start)
+    }
+
+    pub fn transformed(&self, matrix: &Matrix3) -> BezierCurve {
+        let map = |p: &Point| {
+            let (x, y) = matrix.transform_coords(p.x, p.y);
+            Point::new(x, y)
+        };
+        BezierCurve::new(map(&self.start), map(&self.control1), map(&self.control2), map(&self.end))
+    }
+}
+
+impl VectorPath {
+    /// Splits the path into subpaths of cubic curves. Arcs are approximated by cubics
+    /// and joined to the current point by a line, as they are drawn.
+    pub fn subpaths(&self) -> Vec<Subpath> {
+        let mut subpaths = Vec::new();
+        let mut curves: Vec<BezierCurve> = Vec::new();
+        let mut start: Option<Point> = None;
+        let mut current: Option<Point> = None;
+
+        let finish = |curves: &mut Vec<BezierCurve>, start: Option<Point>, closed: bool, subpaths: &mut Vec<Subpath>| {
+            if curves.is_empty() {
+                return;
+            }
+            if closed {
+                if let (Some(first), Some(last)) = (start, curves.last().map(|c| c.end)) {
+                    if first.distance_to(&last) > EPSILON {
+                        curves.push(BezierCurve::from_line(last, first));
+                    }
+                }
+            }
+            subpaths.push(Subpath { curves: std::mem::take(curves), closed });
+        };
+
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Move { point } => {
+                    finish(&mut curves, start, false, &mut subpaths);
+                    start = Some(*point);
+                    current = Some(*point);
+                }
+                PathSegment::Line { point } => {
+                    let from = current.unwrap_or(*point);
+                    start.get_or_insert(from);
+                    if from.distance_to(point) > EPSILON {
+                        curves.push(BezierCurve::from_line(from, *point));
+                    }
+                    current = Some(*point);
+                }
+                PathSegment::Curve { curve } => {
+                    let from = current.unwrap_or(curve.start);
+                    start.get_or_insert(from);
+                    curves.push(BezierCurve { start: from, ..curve.clone() });
+                    current = Some(curve.end);
+                }
+                PathSegment::Arc { center, radius, start_angle, end_angle, clockwise } => {
+                    let sweep = arc_sweep(*start_angle, *end_angle, *clockwise);
+                    let arc_start = Point::new(center.x + radius * start_angle.cos(), center.y + radius * start_angle.sin());
+                    let from = current.unwrap_or(arc_start);
+                    start.get_or_insert(from);
+                    if from.distance_to(&arc_start) > EPSILON {
+                        curves.push(BezierCurve::from_line(from, arc_start));
+                    }
+                    let arc = arc_to_curves(center, *radius, *start_angle, sweep);
+                    current = Some(arc.last().map_or(arc_start, |c| c.end));
+                    curves.extend(arc);
+                }
+                PathSegment::Close => {
+                    finish(&mut curves, start, true, &mut subpaths);
+                    current = start;
+                }
+            }
+        }
+        // `closed` without a trailing close segment still closes the last subpath
+        finish(&mut curves, start, self.closed, &mut subpaths);
+
+        subpaths
+    }
+
+    /// Subpaths as filling sees them: every one closed, with a line back to its start
+    /// where needed.
+    pub fn closed_subpaths(&self) -> Vec<Subpath> {
+        self.subpaths()
+            .into_iter()
+            .map(|mut subpath| {
+                if let (Some(first), Some(last)) = (subpath.curves.first(), subpath.curves.last()) {
+                    if first.start.distance_to(&last.end) > EPSILON {
+                        subpath.curves.push(BezierCurve::from_line(last.end, first.start));
+                    }
+                }
+                subpath.closed = true;
+                subpath
+            })
+            .collect()
+    }
+
+    /// Appends `curves` as a new subpath. Straight curves become `Line` segments and any
+    /// gap between consecutive curves is bridged with a line.
+    pub fn add_curves(&mut self, curves: &[BezierCurve], close: bool) -> &mut Self {
+        let Some(first) = curves.first() else {
+            return self;
+        };
+
+        self.move_to(first.start);
+        let mut current = first.start;
+        for curve in curves {
+            if current.distance_to(&curve.start) > EPSILON {
+                self.line_to(curve.start);
+            }
+            if curve.is_line() {
+                self.line_to(curve.end);
+            } else {
+                self.segments.push(PathSegment::Curve { curve: curve.clone() });
+            }
+            current = curve.end;
+        }
+        if close {
+            self.close();
+        }
+        self
+    }
+
+    pub fn winding_number(&self, point: &Point) -> i32 {
+        winding_number(&self.subpaths(), point)
+    }
+
+    /// Whether `point` is in the filled area under the path's fill rule.
+    pub fn contains_point(&self, point: &Point) -> bool {
+        self.fill_rule.is_inside(self.winding_number(point))
+    }
+
+    /// Tight bounds of the geometry, with curve extrema solved exactly and arcs measured
+    /// as true circles. `None` for an empty path.
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        let mut extents = Extents::new();
+        let mut current: Option<Point> = None;
+
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Move { point } | PathSegment::Line { point } => {
+                    extents.include(point);
+                    current = Some(*point);
+                }
+                PathSegment::Curve { curve } => {
+                    let curve = BezierCurve { start: current.unwrap_or(curve.start), ..curve.clone() };
+                    let bounds = curve.bounds();
+                    extents.include(&Point::new(bounds.x, bounds.y));
+                    extents.include(&Point::new(bounds.x + bounds.width, bounds.y + bounds.height));
+                    current = Some(curve.end);
+                }
+                PathSegment::Arc { center, radius, start_angle, end_angle, clockwise } => {
+                    let sweep = arc_sweep(*start_angle, *end_angle, *clockwise);
+                    if let Some(point) = current {
+                        extents.include(&point);
+                    }
+                    include_arc(&mut extents, center, *radius, *start_angle, sweep);
+                    let end = start_angle + sweep;
+                    current = Some(Point::new(center.x + radius * end.cos(), center.y + radius * end.sin()));
+                }
+                PathSegment::Close => {}
+            }
+        }
+
+        extents.to_bounds()
+    }
+
+    /// Bounds of what the stroke paints: the outline offset by half the width along each
+    /// curve, plus miter tips and square or round caps. Dashes are ignored, so a dashed
+    /// stroke may come out slightly larger than drawn.
+    pub fn stroke_bounds(&self, stroke: &Stroke) -> Option<BoundingBox> {
+        let half = stroke.width / 2.0;
+        if half <= 0.0 {
+            return self.bounds();
+        }
+
+        let mut extents = Extents::new();
+        for subpath in self.subpaths() {
+            let curves = &subpath.curves;
+            for curve in curves {
+                let ts = std::iter::once(0.0).chain(curve.extrema()).chain(std::iter::once(1.0));
+                for t in ts {
+                    let point = curve.point_at(t);
+                    let normal = curve.normal_at(t).scale(half);
+                    extents.include(&point.add(&normal));
+                    extents.include(&point.subtract(&normal));
+                }
+            }
+
+            let joins = if subpath.closed { curves.len() } else { curves.len().saturating_sub(1) };
+            for i in 0..joins {
+                let (incoming, outgoing) = (&curves[i], &curves[(i + 1) % curves.len()]);
+                Self::include_join(&mut extents, incoming, outgoing, stroke, half);
+            }
+
+            if !subpath.closed {
+                if let (Some(first), Some(last)) = (curves.first(), curves.last()) {
+                    Self::include_cap(&mut extents, &first.start, &first.tangent_at(0.0).scale(-1.0), stroke, half);
+                    Self::include_cap(&mut extents, &last.end, &last.tangent_at(1.0), stroke, half);
+                }
+            }
+        }
+
+        extents.to_bounds().or_else(|| self.bounds())
+    }
+
+    /// Maps every point through `matrix`. Arcs survive rotation, reflection and uniform
+    /// scale; any other transform turns them into cubics.
+    pub fn transformed(&self, matrix: &Matrix3) -> VectorPath {
+        let map = |p: &Point| {
+            let (x, y) = matrix.transform_coords(p.x, p.y);
+            Point::new(x, y)
+        };
+        let parts = matrix.decompose();
+        let similarity = parts.skew_x.abs() < EPSILON && (parts.scale_x - parts.scale_y.abs()).abs() < EPSILON;
+        let mirrored = parts.scale_y < 0.0;
+
+        let mut result = VectorPath { segments: Vec::with_capacity(self.segments.len()), ..self.clone() };
+        let mut current: Option<Point> = None;
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Move { point } => {
+                    result.segments.push(PathSegment::Move { point: map(point) });
+                    current = Some(*point);
+                }
+                PathSegment::Line { point } => {
+                    result.segments.push(PathSegment::Line { point: map(point) });
+                    current = Some(*point);
+                }
+                PathSegment::Curve { curve } => {
+                    result.segments.push(PathSegment::Curve { curve: curve.transformed(matrix) });
+                    current = Some(curve.end);
+                }
+                PathSegment::Arc { center, radius, start_angle, end_angle, clockwise } => {
+                    let sweep = arc_sweep(*start_angle, *end_angle, *clockwise);
+                    let end = start_angle + sweep;
+                    if similarity {
+                        // Angles are measured in the mirrored frame when the matrix reflects
+                        let direction = if mirrored { -1.0 } else { 1.0 };
+                        let start = parts.rotation + direction * start_angle;
+                        result.segments.push(PathSegment::Arc {
+                            center: map(center),
+                            radius: radius * parts.scale_x,
+                            start_angle: start,
+                            end_angle: start + direction * sweep,
+                            clockwise: (sweep >= 0.0) != mirrored,
+                        });
+                    } else {
+                        let arc_start = Point::new(center.x + radius * start_angle.cos(), center.y + radius * start_angle.sin());
+                        match current {
+                            None => result.segments.push(PathSegment::Move { point: map(&arc_start) }),
+                            Some(point) if point.distance_to(&arc_start) > EPSILON => {
+                                result.segments.push(PathSegment::Line { point: map(&arc_start) })
+                            }
+                            Some(_) => {}
+                        }
+                        for curve in arc_to_curves(center, *radius, *start_angle, sweep) {
+                            result.segments.push(PathSegment::Curve { curve: curve.transformed(matrix) });
+                        }
+                    }
+                    current = Some(Point::new(center.x + radius * end.cos(), center.y + radius * end.sin()));
+                }
+                PathSegment::Close => result.segments.push(PathSegment::Close),
+            }
+        }
+
+        result
+    }
+
+    fn include_join(extents: &mut Extents, incoming: &BezierCurve, outgoing: &BezierCurve, stroke: &Stroke, half: f64) {
+        let vertex = incoming.end;
+        let d0 = incoming.tangent_at(1.0);
+        let d1 = outgoing.tangent_at(0.0);
+        let turn = d0.x * d1.y - d0.y * d1.x;
+        if turn.abs() < EPSILON && d0.x * d1.x + d0.y * d1.y > 0.0 {
+            return;
+        }
+
+        match stroke.line_join {
+            LineJoin::Round => extents.include_circle(&vertex, half),
+            LineJoin::Bevel => {}
+            LineJoin::Miter => {
+                // The tip sits on the outside of the turn, along the bisector of the normals
+                let n0 = incoming.normal_at(1.0);
+                let n1 = outgoing.normal_at(0.0);
+                let bisector = n0.add(&n1);
+                let length = bisector.x.hypot(bisector.y);
+                let cos_half = length / 2.0;
+                if length < EPSILON || 1.0 / cos_half > MITER_LIMIT {
+                    return;
+                }
+                let side = if turn > 0.0 { -1.0 } else { 1.0 };
+                extents.include(&vertex.add(&bisector.scale(side * half / (cos_half * length))));
+            }
+        }
+    }
+
+    /// `direction` points away from the path, out of the open end.
+    fn include_cap(extents: &mut Extents, end: &Point, direction: &Point, stroke: &Stroke, half: f64) {
+        match stroke.line_cap {
+            LineCap::Butt => {}
+            LineCap::Round => extents.include_circle(end, half),
+            LineCap::Square => {
+                let normal = Point::new(-direction.y, direction.x).scale(half);
+                let tip = end.add(&direction.scale(half));
+                extents.include(&tip.add(&normal));
+                extents.include(&tip.subtract(&normal));
+            }
+        }
+    }
+}
+
+impl VectorShape {
+    /// The shape as a path in its local space, with its top-left bounds corner at the
+    /// origin (polygons keep their own points).
+    pub fn to_path(&self) -> VectorPath {
+        const KAPPA: f64 = 0.5522847498;
+        let mut path = VectorPath::new();
+
+        let ellipse = |path: &mut VectorPath, rx: f64, ry: f64| {
+            let (cx, cy) = (rx, ry);
+            let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+            path.move_to(Point::new(cx + rx, cy))
+                .curve_to(Point::new(cx + rx, cy + ky), Point::new(cx + kx, cy + ry), Point::new(cx, cy + ry))
+                .curve_to(Point::new(cx - kx, cy + ry), Point::new(cx - rx, cy + ky), Point::new(cx - rx, cy))
+                .curve_to(Point::new(cx - rx, cy - ky), Point::new(cx - kx, cy - ry), Point::new(cx, cy - ry))
+                .curve_to(Point::new(cx + kx, cy - ry), Point::new(cx + rx, cy - ky), Point::new(cx + rx, cy))
+                .close();
+        };
+
+        match self {
+            VectorShape::Rectangle { width, height } => {
+                path.move_to(Point::new(0.0, 0.0))
+                    .line_to(Point::new(*width, 0.0))
+                    .line_to(Point::new(*width, *height))
+                    .line_to(Point::new(0.0, *height))
+                    .close();
+            }
+            VectorShape::Circle { radius } => ellipse(&mut path, *radius, *radius),
+            VectorShape::Ellipse { radius_x, radius_y } => ellipse(&mut path, *radius_x, *radius_y),
+            VectorShape::Polygon { points } => {
+                for (i, point) in points.iter().enumerate() {
+                    if i == 0 {
+                        path.move_to(*point);
+                    } else {
+                        path.line_to(*point);
+                    }
+                }
+                if !points.is_empty() {
+                    path.close();
+                }
+            }
+            VectorShape::Star { sides, outer_radius, inner_radius } => {
+                let count = sides * 2;
+                for i in 0..count {
+                    let angle = i as f64 * std::f64::consts::PI / *sides as f64 - FRAC_PI_2;
+                    let radius = if i % 2 == 0 { outer_radius } else { inner_radius };
+                    let point = Point::new(outer_radius + radius * angle.cos(), outer_radius + radius * angle.sin());
+                    if i == 0 {
+                        path.move_to(point);
+                    } else {
+                        path.line_to(point);
+                    }
+                }
+                if count > 0 {
+                    path.close();
+                }
+            }
+        }
+
+        path
+    }
+}
+
+/// The element's geometry as a path in its local space, for paths and shapes.
+pub fn element_path(element: &VectorElement) -> Option<VectorPath> {
+    match element {
+        VectorElement::Path { path, .. } => Some(path.clone()),
+        VectorElement::Shape { shape, .. } => Some(shape.to_path()),
+        VectorElement::Text { .. } | VectorElement::Group { .. } => None,
+    }
+}
+
+/// Canvas bounds of a path or shape computed from its geometry and transform. The
+/// stroke is scaled with the element, as it is drawn. Text and groups return `None`.
+pub fn element_bounds(element: &VectorElement, include_stroke: bool) -> Option<BoundingBox> {
+    let matrix = element.transform().to_matrix();
+    let path = element_path(element)?.transformed(&matrix);
+
+    let stroke = match element {
+        VectorElement::Path { style, .. } | VectorElement::Shape { style, .. } => style.stroke.as_ref(),
+        _ => None,
+    };
+    match stroke.filter(|_| include_stroke) {
+        Some(stroke) => {
+            let scaled = Stroke { width: stroke.width * matrix.determinant().abs().sqrt(), ..stroke.clone() };
+            path.stroke_bounds(&scaled)
+        }
+        None => path.bounds(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_point(actual: Point, expected: Point, tolerance: f64) {
+        assert!(actual.distance_to(&expected) < tolerance, "{:?} != {:?}", actual, expected);
+    }
+
+    fn arch() -> BezierCurve {
+        BezierCurve::new(Point::new(0.0, 0.0), Point::new(0.0, 100.0), Point::new(100.0, 100.0), Point::new(100.0, 0.0))
+    }
+
+    fn quarter_circle() -> BezierCurve {
+        arc_to_curves(&Point::new(0.0, 0.0), 100.0, 0.0, FRAC_PI_2).remove(0)
+    }
+
+    #[test]
+    fn curves_start_and_end_on_their_end_points() {
+        let curve = arch();
+        assert_eq!(curve.point_at(0.0), curve.start);
+        assert_eq!(curve.point_at(1.0), curve.end);
+        // At t = 0.5 the weights are 1/8, 3/8, 3/8, 1/8
+        assert_point(curve.point_at(0.5), Point::new(50.0, 75.0), 1e-9);
+    }
+
+    #[test]
+    fn split_halves_meet_on_the_curve() {
+        let curve = arch();
+        for t in [0.25, 0.5, 0.8] {
+            let (left, right) = curve.split(t);
+            let at_t = curve.point_at(t);
+            assert_point(left.end, at_t, 1e-9);
+            assert_point(right.start, at_t, 1e-9);
+            assert_eq!(left.start, curve.start);
+            assert_eq!(right.end, curve.end);
+
+            // Each half retraces its own stretch of the original
+            assert_point(left.point_at(0.5), curve.point_at(t / 2.0), 1e-9);
+            assert_point(right.point_at(0.5), curve.point_at((1.0 + t) / 2.0), 1e-9);
+        }
+    }
+
+    #[test]
+    fn arc_length_of_lines_and_quarter_circles() {
+        let line = BezierCurve::from_line(Point::new(0.0, 0.0), Point::new(30.0, 40.0));
+        assert!((line.length() - 50.0).abs() < 1e-6);
+
+        // Controls bunched towards one end change the speed but not the length
+        let uneven = BezierCurve::new(Point::new(0.0, 0.0), Point::new(1.0, 0.0), Point::new(2.0, 0.0), Point::new(50.0, 0.0));
+        assert!((uneven.length() - 50.0).abs() < 1e-6);
+
+        // The cubic stays within 0.03% of the radius, so its length is close to πr/2
+        let expected = std::f64::consts::PI * 100.0 / 2.0;
+        assert!((quarter_circle().length() - expected).abs() < 0.05, "{}", quarter_circle().length());
+    }
+
+    #[test]
+    fn nearest_point_to_a_point_off_the_curve() {
+        let curve = quarter_circle();
+
+        // Straight out from the middle of the arc
+        let nearest = curve.nearest_point(&Point::new(100.0, 100.0));
+        let on_arc = 100.0 * std::f64::consts::FRAC_1_SQRT_2;
+        assert!((nearest.t - 0.5).abs() < 1e-6);
+        assert_point(nearest.point, Point::new(on_arc, on_arc), 0.05);
+        assert!((nearest.distance - (100.0 * 2f64.sqrt() - 100.0)).abs() < 0.05);
+
+        // Past the start, the end point itself is closest
+        let nearest = curve.nearest_point(&Point::new(150.0, -50.0));
+        assert_eq!(nearest.t, 0.0);
+        assert_eq!(nearest.point, curve.start);
+    }
+
+    #[test]
+    fn arcs_end_where_their_angles_say() {
+        let center = Point::new(50.0, 50.0);
+
+        // On an empty path the arc starts its own subpath
+        let mut path = VectorPath::new();
+        path.arc_to(center, 10.0, 0.0, FRAC_PI_2, true);
+        assert_eq!(path.segments[0], PathSegment::Move { point: Point::new(60.0, 50.0) });
+        assert_point(path.current_point().unwrap(), Point::new(50.0, 60.0), 1e-9);
+
+        // The long way round ends in the same place, after three quarter turns
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(0.0, 0.0)).arc_to(center, 10.0, 0.0, FRAC_PI_2, false);
+        assert_point(path.current_point().unwrap(), Point::new(50.0, 60.0), 1e-9);
+        let curves = &path.subpaths()[0].curves;
+        assert_eq!(curves.len(), 4);
+        assert_eq!(curves[0], BezierCurve::from_line(Point::new(0.0, 0.0), Point::new(60.0, 50.0)));
+        assert_point(curves[1].end, Point::new(50.0, 40.0), 1e-9);
+        assert_point(curves[3].end, Point::new(50.0, 60.0), 1e-9);
+    }
+
+    fn assert_bounds(actual: Option<BoundingBox>, expected: BoundingBox) {
+        let actual = actual.unwrap();
+        let pairs = [
+            (actual.x, expected.x),
+            (actual.y, expected.y),
+            (actual.width, expected.width),
+            (actual.height, expected.height),
+        ];
+        for (a, b) in pairs {
+            assert!((a - b).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn stroke(width: f64, line_cap: LineCap, line_join: LineJoin) -> Stroke {
+        Stroke { color: "#000".to_string(), width, dash_array: None, line_cap, line_join }
+    }
+
+    #[test]
+    fn curve_bounds_hug_the_curve_not_its_controls() {
+        let curve = arch();
+        // The controls reach y = 100, but the curve turns back at y = 75
+        assert_eq!(curve.extrema(), vec![0.5]);
+        assert_bounds(Some(curve.bounds()), BoundingBox::new(0.0, 0.0, 100.0, 75.0));
+
+        let mut path = VectorPath::new();
+        path.move_to(curve.start).curve_to(curve.control1, curve.control2, curve.end);
+        assert_bounds(path.bounds(), BoundingBox::new(0.0, 0.0, 100.0, 75.0));
+    }
+
+    #[test]
+    fn arc_bounds_include_the_quarter_points_they_pass() {
+        let center = Point::new(0.0, 0.0);
+        let r = 10.0 * std::f64::consts::FRAC_1_SQRT_2;
+        let bounds = |start_angle: f64, end_angle: f64, clockwise: bool| {
+            VectorPath::new().arc_to(center, 10.0, start_angle, end_angle, clockwise).bounds()
+        };
+
+        // Across 0° only
+        assert_bounds(bounds(-FRAC_PI_2 / 2.0, FRAC_PI_2 / 2.0, true), BoundingBox::new(r, -r, 10.0 - r, 2.0 * r));
+        // Through 90°, 180° and 270°
+        assert_bounds(bounds(FRAC_PI_2 / 2.0, -FRAC_PI_2 / 2.0, true), BoundingBox::new(-10.0, -10.0, 10.0 + r, 20.0));
+        // Anticlockwise from 45° to 135° goes through 0°, 270° and 180°, missing 90°
+        assert_bounds(bounds(FRAC_PI_2 / 2.0, 3.0 * FRAC_PI_2 / 2.0, false), BoundingBox::new(-10.0, -10.0, 20.0, 10.0 + r));
+    }
+
+    #[test]
+    fn stroke_bounds_cover_the_caps() {
+        // A 3-4-5 diagonal with a stroke 10 wide: the sides sit at ±(4, -3) from the line
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(30.0, 40.0));
+
+        let bounds = |line_cap| path.stroke_bounds(&stroke(10.0, line_cap, LineJoin::Miter));
+        assert_bounds(bounds(LineCap::Butt), BoundingBox::new(-4.0, -3.0, 38.0, 46.0));
+        assert_bounds(bounds(LineCap::Round), BoundingBox::new(-5.0, -5.0, 40.0, 50.0));
+        // Square caps push the corners out by (3, 4) along the line
+        assert_bounds(bounds(LineCap::Square), BoundingBox::new(-7.0, -7.0, 44.0, 54.0));
+    }
+
+    #[test]
+    fn stroke_bounds_cover_the_joins() {
+        // A right-angled V with its vertex at the bottom, (50, 50)
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(50.0, 50.0)).line_to(Point::new(100.0, 0.0));
+        let side = 5.0 * std::f64::consts::FRAC_1_SQRT_2;
+
+        let bottom = |line_join| {
+            let bounds = path.stroke_bounds(&stroke(10.0, LineCap::Butt, line_join)).unwrap();
+            assert!((bounds.x + side).abs() < 1e-6 && (bounds.y + side).abs() < 1e-6);
+            bounds.y + bounds.height
+        };
+        // The miter tip is half the width over cos 45° below the vertex
+        assert!((bottom(LineJoin::Miter) - (50.0 + 5.0 * 2f64.sqrt())).abs() < 1e-6);
+        assert!((bottom(LineJoin::Round) - 55.0).abs() < 1e-6);
+        assert!((bottom(LineJoin::Bevel) - (50.0 + side)).abs() < 1e-6);
+    }
+}
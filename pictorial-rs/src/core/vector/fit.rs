@@ -0,0 +1,261 @@
+use super::types::*;
+
+const EPSILON: f64 = 1e-9;
+
+/// Fits cubics through `points` to within `tolerance`, with end tangents taken from the
+/// neighboring points.
+pub fn fit_curves(points: &[Point], tolerance: f64) -> Vec<BezierCurve> {
+    let points = distinct(points);
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let start_tangent = direction(&points[0], &points[1]);
+    let end_tangent = direction(&points[points.len() - 2], &points[points.len() - 1]);
+    fit_distinct(&points, start_tangent, end_tangent, tolerance)
+}
+
+/// Fits cubics through `points` to within `tolerance`, leaving the first point along
+/// `start_tangent` and arriving at the last along `end_tangent` (both in the direction
+/// of travel). Uses Schneider's algorithm: a least-squares cubic per run of points,
+/// split at the worst point until every point is close enough.
+pub fn fit_curves_with_tangents(points: &[Point], start_tangent: Point, end_tangent: Point, tolerance: f64) -> Vec<BezierCurve> {
+    let points = distinct(points);
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    fit_distinct(&points, unit(&start_tangent), unit(&end_tangent), tolerance)
+}
+
+fn fit_distinct(points: &[Point], start_tangent: Point, end_tangent: Point, tolerance: f64) -> Vec<BezierCurve> {
+    let mut curves = Vec::new();
+    fit_cubic(points, start_tangent, end_tangent.scale(-1.0), tolerance.max(EPSILON), 0, &mut curves);
+    curves
+}
+
+fn distinct(points: &[Point]) -> Vec<Point> {
+    let mut result: Vec<Point> = Vec::with_capacity(points.len());
+    for point in points {
+        if result.last().is_none_or(|last| last.distance_to(point) > EPSILON) {
+            result.push(*point);
+        }
+    }
+    result
+}
+
+fn unit(vector: &Point) -> Point {
+    let length = vector.x.hypot(vector.y);
+    if length < EPSILON {
+        Point::new(0.0, 0.0)
+    } else {
+        vector.scale(1.0 / length)
+    }
+}
+
+fn direction(from: &Point, to: &Point) -> Point {
+    unit(&to.subtract(from))
+}
+
+fn dot(a: &Point, b: &Point) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+/// `tangent1` leaves the first point into the run; `tangent2` leaves the last point
+/// back into it.
+fn fit_cubic(points: &[Point], tangent1: Point, tangent2: Point, tolerance: f64, depth: u32, curves: &mut Vec<BezierCurve>) {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    if points.len() == 2 {
+        let third = first.distance_to(&last) / 3.0;
+        curves.push(BezierCurve::new(first, first.add(&tangent1.scale(third)), last.add(&tangent2.scale(third)), last));
+        return;
+    }
+
+    let mut parameters = chord_lengths(points);
+    let mut curve = generate_bezier(points, &parameters, &tangent1, &tangent2);
+    let (mut error, mut split) = max_error(points, &curve, &parameters);
+    if error <= tolerance {
+        curves.push(curve);
+        return;
+    }
+
+    // Close misses are often just a poor parameterization, so try improving it first
+    if error <= tolerance * 4.0 {
+        for _ in 0..4 {
+            parameters = reparameterize(points, &parameters, &curve);
+            curve = generate_bezier(points, &parameters, &tangent1, &tangent2);
+            (error, split) = max_error(points, &curve, &parameters);
+            if error <= tolerance {
+                curves.push(curve);
+                return;
+            }
+        }
+    }
+
+    if depth >= 32 {
+        curves.push(curve);
+        return;
+    }
+
+    let split = split.clamp(1, points.len() - 2);
+    let mut center = direction(&points[split + 1], &points[split - 1]);
+    if center.x == 0.0 && center.y == 0.0 {
+        center = unit(&Point::new(-(points[split].y - points[split - 1].y), points[split].x - points[split - 1].x));
+    }
+    fit_cubic(&points[..=split], tangent1, center, tolerance, depth + 1, curves);
+    fit_cubic(&points[split..], center.scale(-1.0), tangent2, tolerance, depth + 1, curves);
+}
+
+fn chord_lengths(points: &[Point]) -> Vec<f64> {
+    let mut parameters = Vec::with_capacity(points.len());
+    let mut total = 0.0;
+    parameters.push(0.0);
+    for pair in points.windows(2) {
+        total += pair[0].distance_to(&pair[1]);
+        parameters.push(total);
+    }
+    if total > EPSILON {
+        for parameter in &mut parameters {
+            *parameter /= total;
+        }
+    }
+    parameters
+}
+
+/// Least-squares control arm lengths along the fixed end tangents.
+fn generate_bezier(points: &[Point], parameters: &[f64], tangent1: &Point, tangent2: &Point) -> BezierCurve {
+    let first = points[0];
+    let last = points[points.len() - 1];
+
+    let (mut c00, mut c01, mut c11, mut x0, mut x1) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (point, u) in points.iter().zip(parameters) {
+        let mu = 1.0 - u;
+        let (b0, b1, b2, b3) = (mu * mu * mu, 3.0 * mu * mu * u, 3.0 * mu * u * u, u * u * u);
+        let a1 = tangent1.scale(b1);
+        let a2 = tangent2.scale(b2);
+        c00 += dot(&a1, &a1);
+        c01 += dot(&a1, &a2);
+        c11 += dot(&a2, &a2);
+
+        let rest = point.subtract(&first.scale(b0 + b1)).subtract(&last.scale(b2 + b3));
+        x0 += dot(&a1, &rest);
+        x1 += dot(&a2, &rest);
+    }
+
+    let determinant = c00 * c11 - c01 * c01;
+    let (mut alpha1, mut alpha2) = if determinant.abs() > EPSILON {
+        ((x0 * c11 - c01 * x1) / determinant, (c00 * x1 - x0 * c01) / determinant)
+    } else {
+        (0.0, 0.0)
+    };
+
+    // Negative or vanishing arms give loops or cusps; fall back to a third of the chord
+    let chord = first.distance_to(&last);
+    if alpha1 < chord * 1e-6 || alpha2 < chord * 1e-6 {
+        alpha1 = chord / 3.0;
+        alpha2 = chord / 3.0;
+    }
+
+    BezierCurve::new(first, first.add(&tangent1.scale(alpha1)), last.add(&tangent2.scale(alpha2)), last)
+}
+
+/// Largest distance from a point to the curve at its parameter, and that point's index.
+fn max_error(points: &[Point], curve: &BezierCurve, parameters: &[f64]) -> (f64, usize) {
+    let mut worst = (0.0, points.len() / 2);
+    for (i, (point, u)) in points.iter().zip(parameters).enumerate().skip(1).take(points.len().saturating_sub(2)) {
+        let distance = curve.point_at(*u).distance_to(point);
+        if distance > worst.0 {
+            worst = (distance, i);
+        }
+    }
+    worst
+}
+
+/// One Newton step per point towards the parameter of its nearest curve point.
+fn reparameterize(points: &[Point], parameters: &[f64], curve: &BezierCurve) -> Vec<f64> {
+    points
+        .iter()
+        .zip(parameters)
+        .map(|(point, u)| {
+            let offset = curve.point_at(*u).subtract(point);
+            let d1 = curve.derivative_at(*u);
+            let d2 = curve.second_derivative_at(*u);
+            let denominator = dot(&d1, &d1) + dot(&offset, &d2);
+            if denominator.abs() < EPSILON {
+                *u
+            } else {
+                (u - dot(&offset, &d1) / denominator).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle_samples(radius: f64, count: usize) -> Vec<Point> {
+        (0..=count)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * i as f64 / count as f64;
+                Point::new(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn too_few_points_fit_nothing() {
+        assert!(fit_curves(&[], 0.1).is_empty());
+        assert!(fit_curves(&[Point::new(1.0, 1.0)], 0.1).is_empty());
+        // Repeated points count once
+        assert!(fit_curves(&[Point::new(1.0, 1.0), Point::new(1.0, 1.0)], 0.1).is_empty());
+    }
+
+    #[test]
+    fn two_points_fit_a_line() {
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(9.0, 0.0));
+        let curves = fit_curves(&[a, b], 0.1);
+        assert_eq!(curves.len(), 1);
+        assert!(curves[0].is_line());
+        assert_eq!((curves[0].start, curves[0].end), (a, b));
+    }
+
+    #[test]
+    fn sampled_circle_fits_within_tolerance() {
+        let radius = 50.0;
+        let tolerance = 0.1;
+        let points = circle_samples(radius, 120);
+        let curves = fit_curves(&points, tolerance);
+        // A dozen cubics at most stand in for the 120 segments
+        assert!(!curves.is_empty() && curves.len() <= 12, "{} curves", curves.len());
+
+        for pair in curves.windows(2) {
+            assert!(pair[0].end.distance_to(&pair[1].start) < 1e-9);
+        }
+        assert_eq!(curves[0].start, points[0]);
+        assert_eq!(curves[curves.len() - 1].end, points[points.len() - 1]);
+
+        for point in &points {
+            let distance = curves.iter().map(|curve| curve.nearest_point(point).distance).fold(f64::INFINITY, f64::min);
+            assert!(distance <= tolerance, "{point:?} is {distance} away");
+        }
+        // Between the samples the curves stay on the circle too
+        for curve in &curves {
+            for t in [0.1, 0.3, 0.5, 0.7, 0.9] {
+                let point = curve.point_at(t);
+                assert!((point.x.hypot(point.y) - radius).abs() <= 2.0 * tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn given_tangents_are_kept() {
+        let points = [Point::new(0.0, 0.0), Point::new(5.0, 3.0), Point::new(10.0, 0.0)];
+        let curves = fit_curves_with_tangents(&points, Point::new(0.0, 2.0), Point::new(0.0, -1.0), 0.01);
+        let start = curves[0].tangent_at(0.0);
+        let end = curves[curves.len() - 1].tangent_at(1.0);
+        assert!(start.x.abs() < 1e-9 && start.y > 0.0);
+        assert!(end.x.abs() < 1e-9 && end.y < 0.0);
+    }
+}
@@ -0,0 +1,303 @@
+use super::engine::*;
+use super::offset::dashed;
+use super::types::*;
+use crate::math::Matrix3;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Average glyph advance as a fraction of the font size, standing in for font metrics.
+const GLYPH_ADVANCE: f64 = 0.6;
+/// How closely dash lengths are measured when testing dashed strokes.
+const DASH_TOLERANCE: f64 = 1e-2;
+
+/// The part of an element a point landed on. The stroke is drawn over the fill, so it
+/// wins where both are hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitPart {
+    Fill,
+    Stroke,
+    Text,
+}
+
+/// Tests `point` (canvas coordinates) against what the element actually paints: the
+/// fill under the path's fill rule, the band the stroke covers and the glyphs of text.
+/// Anything within `tolerance` canvas units counts. Joins and caps are treated as round,
+/// and groups are never hit themselves.
+pub fn hit_test(element: &VectorElement, point: &Point, tolerance: f64) -> Option<HitPart> {
+    let matrix = element.transform().to_matrix();
+    let inverse = matrix.invert()?;
+    let (x, y) = inverse.transform_coords(point.x, point.y);
+    let local = Point::new(x, y);
+    // Tolerance is in canvas units, so undo the element's scale along with everything else
+    let tolerance = tolerance.max(0.0) / matrix.determinant().abs().sqrt();
+
+    match element {
+        VectorElement::Path { path, style, .. } => hit_path(path, style, &local, tolerance),
+        VectorElement::Shape { shape, style, .. } => hit_path(&shape.to_path(), style, &local, tolerance),
+        VectorElement::Text { text, bounding_box, .. } => {
+            let frame = text_frame(text, bounding_box, &matrix, &inverse);
+            hit_text(text, &frame, &local, tolerance).then_some(HitPart::Text)
+        }
+        VectorElement::Group { .. } => None,
+    }
+}
+
+/// The element drawn on top at `point`: the highest `z_index`, and the later one in
+/// `elements` on ties. Hidden and locked elements, and anything inside a hidden or
+/// locked group, are passed over. Groups are found through their children.
+pub fn element_at_point<'a>(elements: &'a [VectorElement], point: &Point, tolerance: f64) -> Option<&'a VectorElement> {
+    let by_id: HashMap<&str, &VectorElement> = elements.iter().map(|element| (element.id(), element)).collect();
+    let selectable = |element: &VectorElement| {
+        std::iter::successors(Some(element), |current| current.parent().and_then(|id| by_id.get(id).copied()))
+            .take(elements.len())
+            .all(|current| current.is_visible() && !current.is_locked())
+    };
+
+    let mut candidates: Vec<&VectorElement> = elements
+        .iter()
+        .filter(|element| !matches!(element, VectorElement::Group { .. }) && selectable(element))
+        .collect();
+    // Stable, so elements sharing a z_index keep their document order
+    candidates.sort_by_key(|element| element.z_index());
+    candidates.into_iter().rev().find(|element| hit_test(element, point, tolerance).is_some())
+}
+
+/// Approximate boxes of the visible glyphs, one row per line of `content`, laid out
+/// from the top-left of `frame`. Without font metrics every glyph gets an average
+/// advance, narrowed where needed so the longest line fits the frame's width.
+pub fn text_glyph_boxes(text: &VectorText, frame: &BoundingBox) -> Vec<BoundingBox> {
+    let lines: Vec<Vec<char>> = text.content.split('\n').map(|line| line.chars().collect()).collect();
+    let longest = lines.iter().map(Vec::len).max().unwrap_or(0);
+    let mut advance = (GLYPH_ADVANCE * text.font_size + text.letter_spacing).max(0.0);
+    if frame.width > 0.0 && longest > 0 {
+        advance = advance.min((frame.width + text.letter_spacing.max(0.0)) / longest as f64);
+    }
+    let line_step = text.font_size * if text.line_height > 0.0 { text.line_height } else { 1.0 };
+
+    let mut boxes = Vec::new();
+    for (row, line) in lines.iter().enumerate() {
+        let width = (line.len() as f64 * advance - text.letter_spacing).max(0.0);
+        let left = match text.text_align {
+            TextAlign::Left | TextAlign::Justify => frame.x,
+            TextAlign::Center => frame.x + (frame.width - width) / 2.0,
+            TextAlign::Right => frame.x + frame.width - width,
+        };
+        let top = frame.y + row as f64 * line_step;
+        for (column, glyph) in line.iter().enumerate() {
+            if !glyph.is_whitespace() {
+                boxes.push(BoundingBox::new(left + column as f64 * advance, top, advance, text.font_size));
+            }
+        }
+    }
+    boxes
+}
+
+fn hit_path(path: &VectorPath, style: &Style, point: &Point, tolerance: f64) -> Option<HitPart> {
+    let stroke = style.stroke.as_ref().filter(|stroke| stroke.width > 0.0);
+    let reach = stroke.map_or(0.0, |stroke| stroke.width / 2.0) + tolerance;
+    let bounds = path.bounds()?;
+    if point.x < bounds.x - reach
+        || point.y < bounds.y - reach
+        || point.x > bounds.x + bounds.width + reach
+        || point.y > bounds.y + bounds.height + reach
+    {
+        return None;
+    }
+
+    if let Some(stroke) = stroke {
+        let mut subpaths = path.subpaths();
+        if let Some(pattern) = &stroke.dash_array {
+            subpaths = dashed(subpaths, pattern, stroke.line_cap != LineCap::Butt, DASH_TOLERANCE);
+        }
+        if distance_to(&subpaths, point) <= reach {
+            return Some(HitPart::Stroke);
+        }
+    }
+
+    if style.fill.is_some() {
+        // Filling closes open subpaths, so their closing lines count as edges too
+        let subpaths = path.closed_subpaths();
+        if path.fill_rule.is_inside(winding_number(&subpaths, point)) || distance_to(&subpaths, point) <= tolerance {
+            return Some(HitPart::Fill);
+        }
+    }
+    None
+}
+
+/// Text on a path is hit within half the font size of the path; other text on its
+/// glyph boxes.
+fn hit_text(text: &VectorText, frame: &BoundingBox, point: &Point, tolerance: f64) -> bool {
+    if let Some(path) = &text.path {
+        return distance_to(&path.subpaths(), point) <= text.font_size / 2.0 + tolerance;
+    }
+    text_glyph_boxes(text, frame).iter().any(|glyph| {
+        point.x >= glyph.x - tolerance
+            && point.y >= glyph.y - tolerance
+            && point.x <= glyph.x + glyph.width + tolerance
+            && point.y <= glyph.y + glyph.height + tolerance
+    })
+}
+
+/// The text's layout box in its own coordinates. `bounding_box` is kept in canvas
+/// space, so it maps straight back when the transform only scales and moves. Under
+/// rotation or skew it is the canvas box around the text, which shares the layout
+/// box's center but not its size, so the size is estimated from the content instead.
+fn text_frame(text: &VectorText, bounding_box: &BoundingBox, matrix: &Matrix3, inverse: &Matrix3) -> BoundingBox {
+    let corners = [
+        (bounding_box.x, bounding_box.y),
+        (bounding_box.x + bounding_box.width, bounding_box.y),
+        (bounding_box.x, bounding_box.y + bounding_box.height),
+        (bounding_box.x + bounding_box.width, bounding_box.y + bounding_box.height),
+    ]
+    .map(|(x, y)| inverse.transform_coords(x, y));
+    let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+    let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for (x, y) in corners {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    let local = BoundingBox::new(min_x, min_y, max_x - min_x, max_y - min_y);
+    if matrix.b == 0.0 && matrix.c == 0.0 {
+        return local;
+    }
+
+    let lines: Vec<&str> = text.content.split('\n').collect();
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let line_step = text.font_size * if text.line_height > 0.0 { text.line_height } else { 1.0 };
+    let width = (longest as f64 * (GLYPH_ADVANCE * text.font_size + text.letter_spacing)).clamp(0.0, local.width);
+    let height = ((lines.len() - 1) as f64 * line_step + text.font_size).clamp(0.0, local.height);
+    let center = local.center();
+    BoundingBox::new(center.x - width / 2.0, center.y - height / 2.0, width, height)
+}
+
+fn distance_to(subpaths: &[Subpath], point: &Point) -> f64 {
+    subpaths
+        .iter()
+        .flat_map(|subpath| subpath.curves.iter())
+        .map(|curve| curve.nearest_point(point).distance)
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(fill: bool, stroke: Option<Stroke>) -> Style {
+        Style {
+            fill: fill.then(|| FillType::Solid { color: "#f00".to_string() }),
+            stroke,
+            shadow: None,
+            opacity: None,
+        }
+    }
+
+    fn stroke(width: f64, dash_array: Option<Vec<f64>>) -> Stroke {
+        Stroke { color: "#000".to_string(), width, dash_array, line_cap: LineCap::Butt, line_join: LineJoin::Miter }
+    }
+
+    /// A 100×50 rectangle with its top-left corner at (x, 0).
+    fn rect(id: &str, x: f64, style: Style, z_index: i32) -> VectorElement {
+        VectorElement::Shape {
+            id: id.to_string(),
+            transform: Transform::translate(x, 0.0),
+            style,
+            bounding_box: BoundingBox::new(x, 0.0, 100.0, 50.0),
+            visible: true,
+            locked: false,
+            z_index,
+            shape: VectorShape::Rectangle { width: 100.0, height: 50.0 },
+            parent: None,
+            pivot: None,
+        }
+    }
+
+    fn group(id: &str, visible: bool, locked: bool, parent: Option<&str>) -> VectorElement {
+        VectorElement::Group {
+            id: id.to_string(),
+            transform: Transform::identity(),
+            style: style(false, None),
+            bounding_box: BoundingBox::new(0.0, 0.0, 100.0, 50.0),
+            visible,
+            locked,
+            z_index: 10,
+            children: Vec::new(),
+            parent: parent.map(str::to_string),
+            pivot: None,
+        }
+    }
+
+    fn inside(mut element: VectorElement, id: &str) -> VectorElement {
+        if let VectorElement::Shape { parent, .. } = &mut element {
+            *parent = Some(id.to_string());
+        }
+        element
+    }
+
+    fn top_id(elements: &[VectorElement], x: f64, y: f64) -> Option<&str> {
+        element_at_point(elements, &Point::new(x, y), 0.0).map(|element| element.id())
+    }
+
+    #[test]
+    fn stroke_wins_over_fill() {
+        let element = rect("a", 0.0, style(true, Some(stroke(4.0, None))), 0);
+        // Just inside the edge both paint, and the stroke is drawn on top
+        assert_eq!(hit_test(&element, &Point::new(1.0, 25.0), 0.0), Some(HitPart::Stroke));
+        assert_eq!(hit_test(&element, &Point::new(-1.5, 25.0), 0.0), Some(HitPart::Stroke));
+        assert_eq!(hit_test(&element, &Point::new(50.0, 25.0), 0.0), Some(HitPart::Fill));
+        assert_eq!(hit_test(&element, &Point::new(-3.0, 25.0), 0.0), None);
+        assert_eq!(hit_test(&element, &Point::new(-3.0, 25.0), 1.5), Some(HitPart::Stroke));
+
+        // Unfilled shapes are only hit on their stroke
+        let outline = rect("b", 0.0, style(false, Some(stroke(4.0, None))), 0);
+        assert_eq!(hit_test(&outline, &Point::new(50.0, 25.0), 0.0), None);
+    }
+
+    #[test]
+    fn dash_gaps_are_not_hit() {
+        let element = rect("a", 0.0, style(false, Some(stroke(2.0, Some(vec![10.0, 10.0])))), 0);
+        assert_eq!(hit_test(&element, &Point::new(5.0, 0.0), 0.0), Some(HitPart::Stroke));
+        assert_eq!(hit_test(&element, &Point::new(15.0, 0.0), 0.0), None);
+        assert_eq!(hit_test(&element, &Point::new(25.0, 0.5), 0.0), Some(HitPart::Stroke));
+    }
+
+    #[test]
+    fn topmost_element_wins() {
+        let elements = [
+            rect("low", 0.0, style(true, None), 1),
+            rect("high", 50.0, style(true, None), 2),
+            rect("tie", 50.0, style(true, None), 2),
+        ];
+        assert_eq!(top_id(&elements, 75.0, 25.0), Some("tie"));
+        assert_eq!(top_id(&elements, 25.0, 25.0), Some("low"));
+        assert_eq!(top_id(&elements, 500.0, 25.0), None);
+    }
+
+    #[test]
+    fn locked_and_hidden_ancestors_are_skipped() {
+        let below = rect("below", 0.0, style(true, None), 0);
+        let child = inside(rect("child", 0.0, style(true, None), 5), "inner");
+
+        // The child's own group is fine, but the one around it is hidden or locked
+        for (visible, locked) in [(false, false), (true, true)] {
+            let elements = [
+                below.clone(),
+                group("outer", visible, locked, None),
+                group("inner", true, false, Some("outer")),
+                child.clone(),
+            ];
+            assert_eq!(top_id(&elements, 10.0, 10.0), Some("below"));
+        }
+
+        let elements = [below.clone(), group("outer", true, false, None), group("inner", true, false, Some("outer")), child];
+        assert_eq!(top_id(&elements, 10.0, 10.0), Some("child"));
+
+        let mut locked = below;
+        if let VectorElement::Shape { locked, .. } = &mut locked {
+            *locked = true;
+        }
+        assert_eq!(top_id(&[locked], 10.0, 10.0), None);
+    }
+}
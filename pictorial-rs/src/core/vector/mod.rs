@@ -1,7 +1,20 @@
 pub mod types;
 pub mod engine;
+pub mod boolean;
+pub mod offset;
+pub mod fit;
+pub mod simplify;
+pub mod pencil;
+pub mod tessellation;
+pub mod hit_test;
 pub mod svg;
 
 pub use types::*;
 pub use engine::*;
+pub use boolean::*;
+pub use fit::*;
+pub use simplify::*;
+pub use pencil::*;
+pub use tessellation::*;
+pub use hit_test::*;
 pub use svg::*; 
\ No newline at end of file
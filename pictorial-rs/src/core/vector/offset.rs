@@ -0,0 +1,430 @@
+use super::boolean::BooleanOperation;
+use super::engine::*;
+use super::types::*;
+use std::f64::consts::{PI, TAU};
+
+const EPSILON: f64 = 1e-9;
+
+impl BezierCurve {
+    /// The curve moved `distance` along its normal, as cubics. Pieces are split until
+    /// each stays within `tolerance` of the true offset; lines offset exactly.
+    pub fn offset(&self, distance: f64, tolerance: f64) -> Vec<BezierCurve> {
+        let mut pieces = Vec::new();
+        offset_into(self, distance, tolerance.max(EPSILON), 0, &mut pieces);
+        pieces
+    }
+}
+
+impl VectorPath {
+    /// The area `stroke` paints, as a filled path in the stroke's color. Dashes are cut
+    /// first, then each piece gets its joins and caps, and overlaps are merged away.
+    pub fn outline_stroke(&self, stroke: &Stroke, tolerance: f64) -> VectorPath {
+        let mut subpaths = self.subpaths();
+        if let Some(pattern) = &stroke.dash_array {
+            subpaths = dashed(subpaths, pattern, stroke.line_cap != LineCap::Butt, tolerance);
+        }
+
+        let mut outline = VectorPath::new();
+        outline.fill_color = Some(stroke.color.clone());
+        outline.opacity = self.opacity;
+        for ring in stroke_rings(&subpaths, stroke, tolerance) {
+            outline.add_curves(&ring, true);
+        }
+
+        outline.boolean(&VectorPath::new(), BooleanOperation::Union, tolerance)
+    }
+
+    /// Grows the filled area by `distance`, or shrinks it when `distance` is negative.
+    /// Corners that move outward get `join`; open subpaths are treated as closed.
+    pub fn offset(&self, distance: f64, join: LineJoin, tolerance: f64) -> VectorPath {
+        if distance.abs() < EPSILON {
+            return self.clone();
+        }
+
+        // The band a stroke of twice the distance paints holds everything within
+        // `distance` of the outline, so add it for growing and cut it for shrinking
+        let stroke = Stroke {
+            color: String::new(),
+            width: 2.0 * distance.abs(),
+            dash_array: None,
+            line_cap: LineCap::Butt,
+            line_join: join,
+        };
+        let mut band = VectorPath::new();
+        for ring in stroke_rings(&self.closed_subpaths(), &stroke, tolerance) {
+            band.add_curves(&ring, true);
+        }
+
+        let operation = if distance > 0.0 { BooleanOperation::Union } else { BooleanOperation::Subtract };
+        self.boolean(&band, operation, tolerance)
+    }
+}
+
+fn offset_into(curve: &BezierCurve, distance: f64, tolerance: f64, depth: u32, pieces: &mut Vec<BezierCurve>) {
+    if curve.is_line() {
+        let normal = curve.normal_at(0.0).scale(distance);
+        pieces.push(BezierCurve::from_line(curve.start.add(&normal), curve.end.add(&normal)));
+        return;
+    }
+
+    let candidate = offset_candidate(curve, distance);
+    let fits = depth >= 6
+        || [0.25, 0.5, 0.75].iter().all(|t| {
+            let point = candidate.point_at(*t);
+            (curve.nearest_point(&point).distance - distance.abs()).abs() <= tolerance
+        });
+    if fits {
+        pieces.push(candidate);
+        return;
+    }
+
+    let (left, right) = curve.split(0.5);
+    offset_into(&left, distance, tolerance, depth + 1, pieces);
+    offset_into(&right, distance, tolerance, depth + 1, pieces);
+}
+
+/// Offset end points with the control arms stretched by how the offset's speed differs
+/// from the curve's, `1 - distance * curvature`.
+fn offset_candidate(curve: &BezierCurve, distance: f64) -> BezierCurve {
+    let stretch = |t: f64| {
+        let d1 = curve.derivative_at(t);
+        let d2 = curve.second_derivative_at(t);
+        let speed = d1.x.hypot(d1.y);
+        if speed < EPSILON {
+            1.0
+        } else {
+            1.0 - distance * (d1.x * d2.y - d1.y * d2.x) / speed.powi(3)
+        }
+    };
+
+    let start = curve.start.add(&curve.normal_at(0.0).scale(distance));
+    let end = curve.end.add(&curve.normal_at(1.0).scale(distance));
+    BezierCurve::new(
+        start,
+        start.add(&curve.control1.subtract(&curve.start).scale(stretch(0.0))),
+        end.subtract(&curve.end.subtract(&curve.control2).scale(stretch(1.0))),
+        end,
+    )
+}
+
+/// Closed outlines whose non-zero fill is the stroked area: one ring around each open
+/// subpath, and an outer and a reversed inner ring for each closed one.
+fn stroke_rings(subpaths: &[Subpath], stroke: &Stroke, tolerance: f64) -> Vec<Vec<BezierCurve>> {
+    let half = stroke.width / 2.0;
+    if half <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut rings = Vec::new();
+    for subpath in subpaths {
+        let forward = &subpath.curves;
+        let (Some(first), Some(last)) = (forward.first(), forward.last()) else {
+            continue;
+        };
+        let backward: Vec<BezierCurve> = forward.iter().rev().map(BezierCurve::reversed).collect();
+
+        let left = offset_side(forward, half, &stroke.line_join, subpath.closed, tolerance);
+        let right = offset_side(&backward, half, &stroke.line_join, subpath.closed, tolerance);
+        if subpath.closed {
+            rings.push(left);
+            rings.push(right);
+            continue;
+        }
+
+        let (left_start, left_end) = (left[0].start, left[left.len() - 1].end);
+        let (right_start, right_end) = (right[0].start, right[right.len() - 1].end);
+        let mut ring = left;
+        ring.extend(cap(&last.end, &last.tangent_at(1.0), left_end, right_start, &stroke.line_cap, half));
+        ring.extend(right);
+        ring.extend(cap(&first.start, &first.tangent_at(0.0).scale(-1.0), right_end, left_start, &stroke.line_cap, half));
+        rings.push(ring);
+    }
+    rings
+}
+
+/// The curves offset by `distance` with joins between them, and around the seam when
+/// `closed`.
+fn offset_side(curves: &[BezierCurve], distance: f64, join: &LineJoin, closed: bool, tolerance: f64) -> Vec<BezierCurve> {
+    let mut side = Vec::new();
+    for (i, curve) in curves.iter().enumerate() {
+        if i > 0 {
+            side.extend(join_curves(&curves[i - 1], curve, distance, join));
+        }
+        side.extend(curve.offset(distance, tolerance));
+    }
+    if closed {
+        if let (Some(last), Some(first)) = (curves.last(), curves.first()) {
+            side.extend(join_curves(last, first, distance, join));
+        }
+    }
+    side
+}
+
+fn join_curves(incoming: &BezierCurve, outgoing: &BezierCurve, distance: f64, join: &LineJoin) -> Vec<BezierCurve> {
+    let vertex = incoming.end;
+    let from = vertex.add(&incoming.normal_at(1.0).scale(distance));
+    let to = vertex.add(&outgoing.normal_at(0.0).scale(distance));
+    if from.distance_to(&to) < EPSILON {
+        return Vec::new();
+    }
+
+    let d0 = incoming.tangent_at(1.0);
+    let d1 = outgoing.tangent_at(0.0);
+    let turn = d0.x * d1.y - d0.y * d1.x;
+    let reversal = turn.abs() < EPSILON;
+    if !reversal && distance * turn > 0.0 {
+        // Inside of the turn: go through the vertex so the overlap stays filled
+        return vec![BezierCurve::from_line(from, vertex), BezierCurve::from_line(vertex, to)];
+    }
+
+    match join {
+        LineJoin::Bevel => vec![BezierCurve::from_line(from, to)],
+        LineJoin::Miter => {
+            if !reversal {
+                let gap = to.subtract(&from);
+                let tip = from.add(&d0.scale((gap.x * d1.y - gap.y * d1.x) / turn));
+                if tip.distance_to(&vertex) <= MITER_LIMIT * distance.abs() {
+                    return vec![BezierCurve::from_line(from, tip), BezierCurve::from_line(tip, to)];
+                }
+            }
+            vec![BezierCurve::from_line(from, to)]
+        }
+        LineJoin::Round => round_between(&vertex, from, to, &d0),
+    }
+}
+
+fn cap(end: &Point, direction: &Point, from: Point, to: Point, line_cap: &LineCap, half: f64) -> Vec<BezierCurve> {
+    match line_cap {
+        LineCap::Butt => vec![BezierCurve::from_line(from, to)],
+        LineCap::Square => {
+            let reach = direction.scale(half);
+            let (from_out, to_out) = (from.add(&reach), to.add(&reach));
+            vec![
+                BezierCurve::from_line(from, from_out),
+                BezierCurve::from_line(from_out, to_out),
+                BezierCurve::from_line(to_out, to),
+            ]
+        }
+        LineCap::Round => round_between(end, from, to, direction),
+    }
+}
+
+/// An arc around `center` from `from` to `to`, taking the short way round, or for a half
+/// turn the way that bulges towards `bulge`.
+fn round_between(center: &Point, from: Point, to: Point, bulge: &Point) -> Vec<BezierCurve> {
+    let radius = from.distance_to(center);
+    let start_angle = (from.y - center.y).atan2(from.x - center.x);
+    let mut sweep = (to.y - center.y).atan2(to.x - center.x) - start_angle;
+    if sweep > PI {
+        sweep -= TAU;
+    } else if sweep < -PI {
+        sweep += TAU;
+    }
+    if (PI - sweep.abs()).abs() < 1e-6 {
+        let middle = start_angle + sweep / 2.0;
+        if middle.cos() * bulge.x + middle.sin() * bulge.y < 0.0 {
+            sweep = -sweep;
+        }
+    }
+
+    let mut curves = arc_to_curves(center, radius, start_angle, sweep);
+    if let Some(first) = curves.first_mut() {
+        first.start = from;
+    }
+    if let Some(last) = curves.last_mut() {
+        last.end = to;
+    }
+    curves
+}
+
+/// Cuts subpaths into the "on" runs of a dash pattern, restarting it on each subpath as
+/// SVG does. Zero-length dashes are kept as tiny runs when `keep_dots`, so round and
+/// square caps still draw them.
+pub(crate) fn dashed(subpaths: Vec<Subpath>, pattern: &[f64], keep_dots: bool, tolerance: f64) -> Vec<Subpath> {
+    let total: f64 = pattern.iter().sum();
+    if pattern.is_empty() || pattern.iter().any(|d| *d < 0.0) || total <= EPSILON {
+        return subpaths;
+    }
+    // An odd-length pattern repeats to make it even, so dashes and gaps alternate
+    let pattern: Vec<f64> = if pattern.len() % 2 == 1 { pattern.repeat(2) } else { pattern.to_vec() };
+
+    let mut dashes = Vec::new();
+    for subpath in subpaths {
+        let mut index = 0;
+        let mut remaining = pattern[0];
+        let mut on = true;
+        let mut dash: Vec<BezierCurve> = Vec::new();
+
+        for curve in &subpath.curves {
+            let length = curve.length_with_tolerance(tolerance);
+            let mut position = 0.0;
+            let mut t = 0.0;
+
+            while length - position > remaining {
+                let t_end = t_at_length(curve, position + remaining, tolerance);
+                if on {
+                    if remaining > EPSILON {
+                        dash.push(curve.subcurve(t, t_end));
+                    } else if keep_dots && dash.is_empty() {
+                        let point = curve.point_at(t_end);
+                        dash.push(BezierCurve::from_line(point, point.add(&curve.tangent_at(t_end).scale(1e-6))));
+                    }
+                    if !dash.is_empty() {
+                        dashes.push(Subpath { curves: std::mem::take(&mut dash), closed: false });
+                    }
+                }
+                position += remaining;
+                t = t_end;
+                index = (index + 1) % pattern.len();
+                remaining = pattern[index];
+                on = !on;
+            }
+
+            if on {
+                dash.push(curve.subcurve(t, 1.0));
+            }
+            remaining -= length - position;
+        }
+
+        if on && !dash.is_empty() {
+            dashes.push(Subpath { curves: dash, closed: false });
+        }
+    }
+    dashes
+}
+
+/// The parameter `length` along the curve, by bisection on the arc length.
+fn t_at_length(curve: &BezierCurve, length: f64, tolerance: f64) -> f64 {
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..24 {
+        let middle = (low + high) / 2.0;
+        if curve.subcurve(0.0, middle).length_with_tolerance(tolerance) < length {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: f64) -> VectorPath {
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(0.0, 0.0))
+            .line_to(Point::new(size, 0.0))
+            .line_to(Point::new(size, size))
+            .line_to(Point::new(0.0, size))
+            .close();
+        path
+    }
+
+    fn line(length: f64) -> VectorPath {
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(length, 0.0));
+        path
+    }
+
+    fn stroke(width: f64, line_cap: LineCap) -> Stroke {
+        Stroke { color: "#000".to_string(), width, dash_array: None, line_cap, line_join: LineJoin::Miter }
+    }
+
+    fn area(path: &VectorPath) -> f64 {
+        let signed: f64 = path
+            .flatten(1e-3)
+            .iter()
+            .map(|ring| ring.iter().zip(ring.iter().cycle().skip(1)).map(|(a, b)| a.x * b.y - b.x * a.y).sum::<f64>() / 2.0)
+            .sum();
+        signed.abs()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    fn assert_bounds(path: &VectorPath, x: f64, y: f64, width: f64, height: f64) {
+        let bounds = path.bounds().unwrap();
+        assert!(
+            close(bounds.x, x) && close(bounds.y, y) && close(bounds.width, width) && close(bounds.height, height),
+            "{bounds:?}"
+        );
+    }
+
+    #[test]
+    fn offsetting_a_square() {
+        let grown = square(10.0).offset(2.0, LineJoin::Miter, 1e-3);
+        assert_bounds(&grown, -2.0, -2.0, 14.0, 14.0);
+        assert!(close(area(&grown), 196.0));
+
+        // Bevels cut a 2×2 triangle off each corner, round joins leave a quarter disc
+        let beveled = square(10.0).offset(2.0, LineJoin::Bevel, 1e-3);
+        assert_bounds(&beveled, -2.0, -2.0, 14.0, 14.0);
+        assert!(close(area(&beveled), 188.0));
+        let rounded = square(10.0).offset(2.0, LineJoin::Round, 1e-3);
+        assert!((area(&rounded) - (180.0 + 4.0 * PI)).abs() < 0.05);
+
+        let shrunk = square(10.0).offset(-2.0, LineJoin::Miter, 1e-3);
+        assert_bounds(&shrunk, 2.0, 2.0, 6.0, 6.0);
+        assert!(close(area(&shrunk), 36.0));
+        assert!(square(10.0).offset(-6.0, LineJoin::Miter, 1e-3).segments.is_empty());
+    }
+
+    #[test]
+    fn outlining_a_straight_line() {
+        let butt = line(10.0).outline_stroke(&stroke(2.0, LineCap::Butt), 1e-3);
+        assert_bounds(&butt, 0.0, -1.0, 10.0, 2.0);
+        assert!(close(area(&butt), 20.0));
+
+        let square = line(10.0).outline_stroke(&stroke(2.0, LineCap::Square), 1e-3);
+        assert_bounds(&square, -1.0, -1.0, 12.0, 2.0);
+        assert!(close(area(&square), 24.0));
+
+        let round = line(10.0).outline_stroke(&stroke(2.0, LineCap::Round), 1e-3);
+        assert_bounds(&round, -1.0, -1.0, 12.0, 2.0);
+        assert!((area(&round) - (20.0 + PI)).abs() < 0.01);
+    }
+
+    #[test]
+    fn miters_fall_back_to_bevels_past_the_limit() {
+        let incoming = BezierCurve::from_line(Point::new(-10.0, 0.0), Point::new(0.0, 0.0));
+
+        // A right angle puts the tip √2 from the corner, well inside the limit
+        let right_angle = BezierCurve::from_line(Point::new(0.0, 0.0), Point::new(0.0, 10.0));
+        let join = join_curves(&incoming, &right_angle, -1.0, &LineJoin::Miter);
+        assert_eq!(join.len(), 2);
+        assert!(close(join[0].end.distance_to(&Point::new(0.0, 0.0)), std::f64::consts::SQRT_2));
+        assert_eq!(join_curves(&incoming, &right_angle, -1.0, &LineJoin::Bevel).len(), 1);
+
+        // Turning back by about 174° would put the tip about 20 away, past the limit of 4
+        let sharp = BezierCurve::from_line(Point::new(0.0, 0.0), Point::new(-10.0, 1.0));
+        let join = join_curves(&incoming, &sharp, -1.0, &LineJoin::Miter);
+        assert_eq!(join, join_curves(&incoming, &sharp, -1.0, &LineJoin::Bevel));
+        assert_eq!(join.len(), 1);
+    }
+
+    #[test]
+    fn dashes_split_along_the_length() {
+        let same = |point: &Point, x: f64, y: f64| close(point.x, x) && close(point.y, y);
+
+        let dashes = dashed(line(10.0).subpaths(), &[3.0, 2.0], false, 1e-3);
+        assert_eq!(dashes.len(), 2);
+        assert!(same(&dashes[0].curves[0].start, 0.0, 0.0) && same(&dashes[0].curves[0].end, 3.0, 0.0));
+        assert!(same(&dashes[1].curves[0].start, 5.0, 0.0) && same(&dashes[1].curves[0].end, 8.0, 0.0));
+
+        // An odd pattern repeats, so [2] dashes 2 on, 2 off
+        assert_eq!(dashed(line(10.0).subpaths(), &[2.0], false, 1e-3).len(), 3);
+
+        // A gap skips over a corner, and a dash carries on around one
+        let mut corner = VectorPath::new();
+        corner.move_to(Point::new(0.0, 0.0)).line_to(Point::new(4.0, 0.0)).line_to(Point::new(4.0, 6.0));
+        let dashes = dashed(corner.subpaths(), &[3.0, 2.0], false, 1e-3);
+        assert_eq!(dashes.len(), 2);
+        assert!(same(&dashes[1].curves[0].start, 4.0, 1.0) && same(&dashes[1].curves[0].end, 4.0, 4.0));
+
+        let dashes = dashed(corner.subpaths(), &[5.0, 1.0], false, 1e-3);
+        assert_eq!(dashes[0].curves.len(), 2);
+        assert!(same(&dashes[0].curves[1].end, 4.0, 1.0));
+    }
+}
@@ -0,0 +1,367 @@
+use super::boolean::BooleanOperation;
+use super::engine::*;
+use super::fit::*;
+use super::types::*;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+const EPSILON: f64 = 1e-9;
+
+/// Sharper turns than this (radians) always become corners.
+const CORNER_ANGLE: f64 = 1.75;
+/// Turns sharper than this (radians) become corners when the pen paused on them.
+const PAUSED_CORNER_ANGLE: f64 = 0.7;
+/// How long the pen has to rest on a point for it to count as a pause, in milliseconds.
+const PAUSE: f64 = 100.0;
+
+/// One pointer sample from the pencil tool. `time` is in milliseconds and `pressure` runs
+/// from 0 to 1 where the device reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PencilSample {
+    pub x: f64,
+    pub y: f64,
+    pub time: f64,
+    #[serde(default)]
+    pub pressure: Option<f64>,
+}
+
+/// A sample after merging its near-duplicates, remembering how long the pen rested there.
+#[derive(Debug, Clone, Copy)]
+struct StrokePoint {
+    point: Point,
+    dwell: f64,
+    pressure: f64,
+}
+
+/// How closely a pencil stroke follows the hand: fidelity 1 keeps within half a pixel
+/// of the samples, fidelity 0 smooths over ten pixels of wobble.
+pub fn pencil_tolerance(fidelity: f64) -> f64 {
+    0.5 + (1.0 - fidelity.clamp(0.0, 1.0)) * 9.5
+}
+
+/// Fits raw pencil samples with cubics. Sharp turns, and gentler ones where the pen
+/// paused, stay as corners; everything else is fitted smoothly to within
+/// `pencil_tolerance(fidelity)`.
+pub fn fit_pencil(samples: &[PencilSample], fidelity: f64) -> VectorPath {
+    let tolerance = pencil_tolerance(fidelity);
+    let points = stroke_points(samples, tolerance);
+
+    let mut path = VectorPath::new();
+    let curves = fit_sections(&points, tolerance);
+    if !curves.is_empty() {
+        path.add_curves(&curves, false);
+    } else if let Some(first) = points.first() {
+        path.move_to(first.point);
+    }
+    path
+}
+
+/// A filled outline around the fitted stroke whose width follows the pen pressure, up to
+/// `width` at full pressure. Samples without pressure draw at full width; the ends are
+/// rounded.
+pub fn pencil_outline(samples: &[PencilSample], fidelity: f64, width: f64) -> VectorPath {
+    const STEPS: usize = 16;
+
+    let tolerance = pencil_tolerance(fidelity);
+    let points = stroke_points(samples, tolerance);
+    let mut outline = VectorPath::new();
+    if points.is_empty() || width <= 0.0 {
+        return outline;
+    }
+
+    let curves = fit_sections(&points, tolerance);
+    if curves.is_empty() {
+        let radius = width * points[0].pressure / 2.0;
+        if radius > EPSILON {
+            outline.add_curves(&arc_to_curves(&points[0].point, radius, 0.0, TAU), true);
+        }
+        return outline;
+    }
+
+    // Pressure is spread over the fitted curves by the fraction of the stroke walked
+    let sample_lengths = cumulative_lengths(points.iter().map(|p| p.point));
+    let sample_total = sample_lengths[sample_lengths.len() - 1];
+    let curve_lengths: Vec<f64> = curves.iter().map(|c| c.length()).collect();
+    let curve_total: f64 = curve_lengths.iter().sum();
+
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    let mut walked = 0.0;
+    for (curve, length) in curves.iter().zip(&curve_lengths) {
+        for step in 0..=STEPS {
+            if step == 0 && !left.is_empty() {
+                continue;
+            }
+            let t = step as f64 / STEPS as f64;
+            let fraction = if curve_total > EPSILON { (walked + length * t) / curve_total } else { 0.0 };
+            let pressure = pressure_at(&points, &sample_lengths, fraction * sample_total);
+            let point = curve.point_at(t);
+            let normal = curve.normal_at(t).scale(width * pressure / 2.0);
+            left.push(point.add(&normal));
+            right.push(point.subtract(&normal));
+        }
+        walked += length;
+    }
+
+    // Sides are fitted tighter than the spine so the width reads faithfully
+    let side_tolerance = (tolerance / 4.0).max(0.25);
+    let left = fit_curves(&left, side_tolerance);
+    right.reverse();
+    let right = fit_curves(&right, side_tolerance);
+    let (first, last) = (&curves[0], &curves[curves.len() - 1]);
+
+    let mut ring = left;
+    if let (Some(from), Some(to)) = (ring.last().map(|c| c.end), right.first().map(|c| c.start)) {
+        ring.extend(round_cap(&last.end, from, to));
+    }
+    ring.extend(right);
+    if let (Some(from), Some(to)) = (ring.last().map(|c| c.end), ring.first().map(|c| c.start)) {
+        ring.extend(round_cap(&first.start, from, to));
+    }
+    outline.add_curves(&ring, true);
+
+    // Tight turns fold the sides over each other, so merge the overlaps away. The sides
+    // are only as exact as their fit, so edges closer than that count as touching
+    outline.boolean(&VectorPath::new(), BooleanOperation::Union, side_tolerance)
+}
+
+/// Merges samples closer than a quarter of `tolerance`, keeping how long the pen stayed
+/// and the average pressure.
+fn stroke_points(samples: &[PencilSample], tolerance: f64) -> Vec<StrokePoint> {
+    let spacing = tolerance / 4.0;
+    let mut points: Vec<StrokePoint> = Vec::with_capacity(samples.len());
+    let mut arrived = 0.0;
+    let mut pressures = Vec::new();
+
+    for sample in samples {
+        let point = Point::new(sample.x, sample.y);
+        let pressure = sample.pressure.unwrap_or(1.0).clamp(0.0, 1.0);
+        match points.last_mut() {
+            Some(last) if last.point.distance_to(&point) < spacing => {
+                last.dwell = (sample.time - arrived).max(0.0);
+                pressures.push(pressure);
+                last.pressure = pressures.iter().sum::<f64>() / pressures.len() as f64;
+            }
+            _ => {
+                arrived = sample.time;
+                pressures = vec![pressure];
+                points.push(StrokePoint { point, dwell: 0.0, pressure });
+            }
+        }
+    }
+    points
+}
+
+/// Splits the stroke at corners and fits each section.
+fn fit_sections(points: &[StrokePoint], tolerance: f64) -> Vec<BezierCurve> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+
+    let positions: Vec<Point> = points.iter().map(|p| p.point).collect();
+    let reach = tolerance * 2.0;
+    let corners = find_corners(points, reach);
+
+    let mut curves = Vec::new();
+    let mut bounds = vec![0];
+    bounds.extend(corners);
+    bounds.push(points.len() - 1);
+    for pair in bounds.windows(2) {
+        let section = &positions[pair[0]..=pair[1]];
+        if section.len() < 2 {
+            continue;
+        }
+        // Tangents from a little way in, so jitter at the ends doesn't tilt them
+        let start = section[0];
+        let end = section[section.len() - 1];
+        let ahead = section.iter().find(|p| p.distance_to(&start) >= reach).unwrap_or(&end);
+        let behind = section.iter().rev().find(|p| p.distance_to(&end) >= reach).unwrap_or(&start);
+        curves.extend(fit_curves_with_tangents(section, ahead.subtract(&start), end.subtract(behind), tolerance));
+    }
+    curves
+}
+
+/// Indices where the stroke turns sharply, comparing directions measured `reach` either
+/// side so hand jitter doesn't register. Nearby candidates keep only the sharpest.
+fn find_corners(points: &[StrokePoint], reach: f64) -> Vec<usize> {
+    let mut candidates: Vec<(usize, f64)> = Vec::new();
+    for i in 1..points.len() - 1 {
+        let here = points[i].point;
+        let Some(before) = points[..i].iter().rev().find(|p| p.point.distance_to(&here) >= reach) else {
+            continue;
+        };
+        let Some(after) = points[i + 1..].iter().find(|p| p.point.distance_to(&here) >= reach) else {
+            continue;
+        };
+
+        let incoming = here.subtract(&before.point);
+        let outgoing = after.point.subtract(&here);
+        let turn = (incoming.x * outgoing.y - incoming.y * outgoing.x)
+            .atan2(incoming.x * outgoing.x + incoming.y * outgoing.y)
+            .abs();
+        if turn > CORNER_ANGLE || (turn > PAUSED_CORNER_ANGLE && points[i].dwell >= PAUSE) {
+            candidates.push((i, turn));
+        }
+    }
+
+    let mut corners: Vec<(usize, f64)> = Vec::new();
+    for (index, turn) in candidates {
+        match corners.last_mut() {
+            Some(last) if points[last.0].point.distance_to(&points[index].point) < reach => {
+                if turn > last.1 {
+                    *last = (index, turn);
+                }
+            }
+            _ => corners.push((index, turn)),
+        }
+    }
+    corners.into_iter().map(|(index, _)| index).collect()
+}
+
+fn cumulative_lengths(points: impl Iterator<Item = Point>) -> Vec<f64> {
+    let mut lengths = Vec::new();
+    let mut previous: Option<Point> = None;
+    let mut total = 0.0;
+    for point in points {
+        if let Some(previous) = previous {
+            total += previous.distance_to(&point);
+        }
+        lengths.push(total);
+        previous = Some(point);
+    }
+    lengths
+}
+
+/// Pressure `distance` along the sampled stroke, interpolated between samples.
+fn pressure_at(points: &[StrokePoint], lengths: &[f64], distance: f64) -> f64 {
+    let next = lengths.partition_point(|l| *l < distance).min(points.len() - 1);
+    if next == 0 {
+        return points[0].pressure;
+    }
+    let span = lengths[next] - lengths[next - 1];
+    let t = if span > EPSILON { (distance - lengths[next - 1]) / span } else { 1.0 };
+    points[next - 1].pressure + (points[next].pressure - points[next - 1].pressure) * t.clamp(0.0, 1.0)
+}
+
+/// A half circle around `center` from one side of the stroke to the other, bulging away
+/// from the stroke.
+fn round_cap(center: &Point, from: Point, to: Point) -> Vec<BezierCurve> {
+    let radius = from.distance_to(center);
+    if radius < EPSILON || from.distance_to(&to) < EPSILON {
+        return vec![BezierCurve::from_line(from, to)];
+    }
+    let start_angle = (from.y - center.y).atan2(from.x - center.x);
+    let end_angle = (to.y - center.y).atan2(to.x - center.x);
+    // The sides run along +normal going forward and -normal coming back, so the way
+    // round the end is always towards decreasing angles
+    let mut curves = arc_to_curves(center, radius, start_angle, arc_sweep(start_angle, end_angle, false));
+    if let Some(first) = curves.first_mut() {
+        first.start = from;
+    }
+    if let Some(last) = curves.last_mut() {
+        last.end = to;
+    }
+    curves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points every 2 units along the polyline through `corners`.
+    fn polyline(corners: &[(f64, f64)]) -> Vec<Point> {
+        let mut points = vec![Point::new(corners[0].0, corners[0].1)];
+        for pair in corners.windows(2) {
+            let (from, to) = (Point::new(pair[0].0, pair[0].1), Point::new(pair[1].0, pair[1].1));
+            let steps = (from.distance_to(&to) / 2.0).round() as usize;
+            for step in 1..=steps {
+                let t = step as f64 / steps as f64;
+                points.push(from.add(&to.subtract(&from).scale(t)));
+            }
+        }
+        points
+    }
+
+    /// One sample every 10ms, resting `pause` milliseconds on the point at `pause_at`.
+    fn samples(points: &[Point], pause_at: usize, pause: f64) -> Vec<PencilSample> {
+        let mut time = 0.0;
+        let mut samples = Vec::new();
+        for (i, point) in points.iter().enumerate() {
+            samples.push(PencilSample { x: point.x, y: point.y, time, pressure: None });
+            if i == pause_at && pause > 0.0 {
+                time += pause;
+                samples.push(PencilSample { x: point.x, y: point.y, time, pressure: None });
+            }
+            time += 10.0;
+        }
+        samples
+    }
+
+    fn corners(samples: &[PencilSample]) -> Vec<Point> {
+        let tolerance = pencil_tolerance(1.0);
+        let points = stroke_points(samples, tolerance);
+        find_corners(&points, tolerance * 2.0).into_iter().map(|index| points[index].point).collect()
+    }
+
+    #[test]
+    fn sharp_turns_are_corners() {
+        // Turning by 120° is past the corner angle, paused or not
+        let sharp = polyline(&[(0.0, 0.0), (50.0, 0.0), (25.0, 25.0 * 3f64.sqrt())]);
+        assert_eq!(corners(&samples(&sharp, 0, 0.0)), [Point::new(50.0, 0.0)]);
+
+        let smooth = polyline(&[(0.0, 0.0), (50.0, 0.0), (100.0, 10.0)]);
+        assert!(corners(&samples(&smooth, 25, 500.0)).is_empty());
+    }
+
+    #[test]
+    fn pausing_turns_gentler_bends_into_corners() {
+        // A right angle is gentler than the corner angle but sharper than the paused one
+        let bend = polyline(&[(0.0, 0.0), (50.0, 0.0), (50.0, 50.0)]);
+        assert!(corners(&samples(&bend, 25, 0.0)).is_empty());
+        assert!(corners(&samples(&bend, 25, 99.0)).is_empty());
+        assert_eq!(corners(&samples(&bend, 25, PAUSE)), [Point::new(50.0, 0.0)]);
+
+        // The fitted path keeps the corner as a joint between curves
+        let path = fit_pencil(&samples(&bend, 25, PAUSE), 1.0);
+        let curves = &path.subpaths()[0].curves;
+        assert!(curves.iter().any(|curve| curve.end.distance_to(&Point::new(50.0, 0.0)) < 1e-9));
+    }
+
+    #[test]
+    fn pressure_is_interpolated_between_samples() {
+        let points = [
+            StrokePoint { point: Point::new(0.0, 0.0), dwell: 0.0, pressure: 0.2 },
+            StrokePoint { point: Point::new(10.0, 0.0), dwell: 0.0, pressure: 0.6 },
+            StrokePoint { point: Point::new(30.0, 0.0), dwell: 0.0, pressure: 1.0 },
+        ];
+        let lengths = [0.0, 10.0, 30.0];
+        assert!((pressure_at(&points, &lengths, 0.0) - 0.2).abs() < 1e-9);
+        assert!((pressure_at(&points, &lengths, 5.0) - 0.4).abs() < 1e-9);
+        assert!((pressure_at(&points, &lengths, 20.0) - 0.8).abs() < 1e-9);
+        assert!((pressure_at(&points, &lengths, 40.0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn outline_width_follows_pressure() {
+        // Pressure rises evenly from 0.2 to 1 along a 100 long line, drawn 10 wide
+        let line = polyline(&[(0.0, 0.0), (100.0, 0.0)]);
+        let mut samples = samples(&line, 0, 0.0);
+        for sample in &mut samples {
+            sample.pressure = Some(0.2 + 0.8 * sample.x / 100.0);
+        }
+        let outline = pencil_outline(&samples, 1.0, 10.0);
+
+        // Half widths of 1.8 at x = 20 and 4.2 at x = 80
+        assert!(outline.contains_point(&Point::new(20.0, 1.4)));
+        assert!(!outline.contains_point(&Point::new(20.0, 2.2)));
+        assert!(outline.contains_point(&Point::new(80.0, -3.8)));
+        assert!(!outline.contains_point(&Point::new(80.0, -4.6)));
+
+        // Without pressure the whole stroke is full width
+        for sample in &mut samples {
+            sample.pressure = None;
+        }
+        let outline = pencil_outline(&samples, 1.0, 10.0);
+        assert!(outline.contains_point(&Point::new(20.0, 4.6)));
+        assert!(!outline.contains_point(&Point::new(20.0, 5.4)));
+    }
+}
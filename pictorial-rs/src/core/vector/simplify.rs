@@ -0,0 +1,414 @@
+use super::engine::*;
+use super::fit::*;
+use super::types::*;
+
+const EPSILON: f64 = 1e-9;
+
+/// Curves meeting at less than this angle (radians) count as one smooth run when refitting.
+const SMOOTH_JOIN: f64 = 0.035;
+
+/// Drops points of a polyline that sit within `tolerance` of the line through their
+/// neighbors (Ramer–Douglas–Peucker). The first and last points are always kept.
+pub fn simplify_polyline(points: &[Point], tolerance: f64) -> Vec<Point> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let farthest = (first + 1..last)
+            .map(|i| (i, distance_to_segment(&points[i], &points[first], &points[last])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((index, distance)) = farthest {
+            if distance > tolerance {
+                keep[index] = true;
+                stack.push((first, index));
+                stack.push((index, last));
+            }
+        }
+    }
+
+    points.iter().zip(keep).filter(|(_, keep)| *keep).map(|(point, _)| *point).collect()
+}
+
+fn distance_to_segment(point: &Point, start: &Point, end: &Point) -> f64 {
+    let segment = end.subtract(start);
+    let length_squared = segment.x * segment.x + segment.y * segment.y;
+    if length_squared < EPSILON {
+        return point.distance_to(start);
+    }
+    let offset = point.subtract(start);
+    let t = ((offset.x * segment.x + offset.y * segment.y) / length_squared).clamp(0.0, 1.0);
+    point.distance_to(&start.add(&segment.scale(t)))
+}
+
+/// Turning angle between two directions, in radians.
+fn turn_angle(incoming: &Point, outgoing: &Point) -> f64 {
+    let cross = incoming.x * outgoing.y - incoming.y * outgoing.x;
+    let dot = incoming.x * outgoing.x + incoming.y * outgoing.y;
+    cross.atan2(dot).abs()
+}
+
+/// Segments of a path collected into runs of the same kind, so each run can be
+/// rebuilt as a whole.
+struct Runs {
+    segments: Vec<PathSegment>,
+    current: Option<Point>,
+    subpath_start: Option<Point>,
+    lines: Vec<Point>,
+    curves: Vec<BezierCurve>,
+}
+
+impl Runs {
+    fn new() -> Runs {
+        Runs {
+            segments: Vec::new(),
+            current: None,
+            subpath_start: None,
+            lines: Vec::new(),
+            curves: Vec::new(),
+        }
+    }
+
+    fn push_curves(&mut self, curves: &[BezierCurve]) {
+        for curve in curves {
+            if curve.is_line() {
+                self.segments.push(PathSegment::Line { point: curve.end });
+            } else {
+                self.segments.push(PathSegment::Curve { curve: curve.clone() });
+            }
+        }
+    }
+
+    fn push_lines(&mut self, points: &[Point]) {
+        self.segments.extend(points.iter().skip(1).map(|point| PathSegment::Line { point: *point }));
+    }
+}
+
+impl VectorPath {
+    /// Drops detail smaller than `tolerance`. Straight runs lose points by
+    /// Ramer–Douglas–Peucker, and runs of smoothly joined curves are refitted with fewer
+    /// cubics. Corners, arcs and the subpath structure are kept.
+    pub fn simplify(&self, tolerance: f64) -> VectorPath {
+        let tolerance = tolerance.max(EPSILON);
+        let flush_lines = |runs: &mut Runs| {
+            let simplified = simplify_polyline(&std::mem::take(&mut runs.lines), tolerance);
+            runs.push_lines(&simplified);
+        };
+        let flush_curves = |runs: &mut Runs| {
+            let curves = std::mem::take(&mut runs.curves);
+            let refitted = refit(&curves, tolerance);
+            runs.push_curves(refitted.as_deref().unwrap_or(&curves));
+        };
+
+        self.rebuild(|runs, segment| match segment {
+            Some(PathSegment::Line { point }) => {
+                flush_curves(runs);
+                if runs.lines.is_empty() {
+                    runs.lines.push(runs.current.unwrap_or(*point));
+                }
+                runs.lines.push(*point);
+                true
+            }
+            Some(PathSegment::Curve { curve }) => {
+                flush_lines(runs);
+                let curve = BezierCurve { start: runs.current.unwrap_or(curve.start), ..curve.clone() };
+                let smooth = runs
+                    .curves
+                    .last()
+                    .is_none_or(|last| turn_angle(&last.tangent_at(1.0), &curve.tangent_at(0.0)) < SMOOTH_JOIN);
+                if !smooth {
+                    flush_curves(runs);
+                }
+                runs.curves.push(curve);
+                true
+            }
+            _ => {
+                flush_lines(runs);
+                flush_curves(runs);
+                false
+            }
+        })
+    }
+
+    /// Replaces straight runs with cubics fitted to within `tolerance` of their points.
+    /// Wherever a run turns by more than `corner_angle` (radians) it keeps a sharp
+    /// corner; a closed subpath with no corners comes out smooth all the way round.
+    pub fn smooth(&self, corner_angle: f64, tolerance: f64) -> VectorPath {
+        let tolerance = tolerance.max(EPSILON);
+        let flush = |runs: &mut Runs, closed: bool| {
+            let points = std::mem::take(&mut runs.lines);
+            let curves = smooth_polyline(&points, corner_angle, closed, tolerance);
+            runs.push_curves(&curves);
+        };
+        // A subpath made only of lines can be smoothed across its seam
+        let flush_closing = |runs: &mut Runs| {
+            let whole = runs.segments.last().is_some_and(|s| matches!(s, PathSegment::Move { .. }));
+            let ring = whole && runs.lines.first().is_some_and(|first| Some(*first) == runs.subpath_start);
+            if ring {
+                let start = runs.lines[0];
+                if runs.lines.last().is_some_and(|last| last.distance_to(&start) > EPSILON) {
+                    runs.lines.push(start);
+                }
+            }
+            flush(runs, ring);
+        };
+
+        self.rebuild(|runs, segment| match segment {
+            Some(PathSegment::Line { point }) => {
+                if runs.lines.is_empty() {
+                    runs.lines.push(runs.current.unwrap_or(*point));
+                }
+                runs.lines.push(*point);
+                true
+            }
+            Some(PathSegment::Close) => {
+                flush_closing(runs);
+                false
+            }
+            None if self.closed => {
+                flush_closing(runs);
+                false
+            }
+            _ => {
+                flush(runs, false);
+                false
+            }
+        })
+    }
+
+    /// Replays the segments through `collect`, which returns `true` when it has taken a
+    /// segment into a run; other segments are copied over as they are. A final `None`
+    /// marks the end of the path.
+    fn rebuild(&self, mut collect: impl FnMut(&mut Runs, Option<&PathSegment>) -> bool) -> VectorPath {
+        let mut runs = Runs::new();
+        for segment in &self.segments {
+            let taken = collect(&mut runs, Some(segment));
+            match segment {
+                PathSegment::Move { point } => {
+                    runs.subpath_start = Some(*point);
+                    runs.current = Some(*point);
+                }
+                PathSegment::Line { point } => runs.current = Some(*point),
+                PathSegment::Curve { curve } => runs.current = Some(curve.end),
+                PathSegment::Arc { center, radius, start_angle, end_angle, clockwise } => {
+                    let end = start_angle + arc_sweep(*start_angle, *end_angle, *clockwise);
+                    runs.current = Some(Point::new(center.x + radius * end.cos(), center.y + radius * end.sin()));
+                }
+                PathSegment::Close => runs.current = runs.subpath_start,
+            }
+            if !taken {
+                runs.segments.push(segment.clone());
+            }
+        }
+        collect(&mut runs, None);
+
+        VectorPath { segments: runs.segments, ..self.clone() }
+    }
+}
+
+/// Refits a smooth run of curves from points sampled along it, or `None` when that
+/// doesn't take fewer curves.
+fn refit(curves: &[BezierCurve], tolerance: f64) -> Option<Vec<BezierCurve>> {
+    const SAMPLES: usize = 12;
+
+    let (first, last) = (curves.first()?, curves.last()?);
+    if curves.len() < 2 {
+        return None;
+    }
+
+    let mut points = vec![first.start];
+    for curve in curves {
+        points.extend((1..=SAMPLES).map(|i| curve.point_at(i as f64 / SAMPLES as f64)));
+    }
+    let mut fitted = fit_curves_with_tangents(&points, first.tangent_at(0.0), last.tangent_at(1.0), tolerance);
+    if fitted.is_empty() || fitted.len() >= curves.len() {
+        return None;
+    }
+
+    // Keep the run's end points exact so neighbors still meet it
+    fitted[0].start = first.start;
+    let count = fitted.len();
+    fitted[count - 1].end = last.end;
+    Some(fitted)
+}
+
+/// Fits cubics to a polyline, splitting it at corners sharper than `corner_angle`. For
+/// a `closed` ring (last point equal to the first) the seam is treated like any other
+/// vertex.
+fn smooth_polyline(points: &[Point], corner_angle: f64, closed: bool, tolerance: f64) -> Vec<BezierCurve> {
+    if points.len() < 2 {
+        return Vec::new();
+    }
+    if points.len() == 2 {
+        return vec![BezierCurve::from_line(points[0], points[1])];
+    }
+
+    let count = points.len();
+    let heading = |i: usize| points[i + 1].subtract(&points[i]);
+    let is_corner = |i: usize| {
+        if i == 0 || i == count - 1 {
+            return !closed || turn_angle(&heading(count - 2), &heading(0)) > corner_angle;
+        }
+        turn_angle(&heading(i - 1), &heading(i)) > corner_angle
+    };
+
+    let mut corners: Vec<usize> = (0..count).filter(|i| is_corner(*i)).collect();
+    if closed && corners.is_empty() {
+        // Smooth all the way round: both ends share the tangent across the seam
+        let tangent = points[1].subtract(&points[count - 2]);
+        return fit_curves_with_tangents(points, tangent, tangent, tolerance);
+    }
+
+    let ring: Vec<Point>;
+    let points = if closed && corners[0] != 0 {
+        // Start the ring at a corner so every section runs corner to corner
+        let start = corners[0];
+        ring = points[start..count - 1].iter().chain(points[..=start].iter()).copied().collect();
+        corners = corners.iter().map(|i| (i + count - 1 - start) % (count - 1)).collect();
+        corners.push(count - 1);
+        corners.sort_unstable();
+        corners.dedup();
+        &ring[..]
+    } else {
+        points
+    };
+
+    corners
+        .windows(2)
+        .flat_map(|pair| {
+            let section = &points[pair[0]..=pair[1]];
+            if section.len() == 2 {
+                vec![BezierCurve::from_line(section[0], section[1])]
+            } else {
+                fit_curves(section, tolerance)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collinear_points_reduce_to_their_ends() {
+        let points: Vec<Point> = (0..=10).map(|i| Point::new(i as f64, 2.0 * i as f64)).collect();
+        assert_eq!(simplify_polyline(&points, 0.01), vec![points[0], points[10]]);
+    }
+
+    #[test]
+    fn points_off_the_line_are_kept_past_the_tolerance() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 0.52),
+            Point::new(4.0, 1.0),
+            Point::new(6.0, 0.52),
+            Point::new(8.0, 0.0),
+        ];
+        assert_eq!(simplify_polyline(&points, 0.1), vec![points[0], points[2], points[4]]);
+        assert_eq!(simplify_polyline(&points, 2.0), vec![points[0], points[4]]);
+    }
+
+    #[test]
+    fn short_polylines_are_unchanged() {
+        assert!(simplify_polyline(&[], 1.0).is_empty());
+        let one = [Point::new(1.0, 2.0)];
+        assert_eq!(simplify_polyline(&one, 1.0), one);
+        let two = [Point::new(1.0, 2.0), Point::new(3.0, 4.0)];
+        assert_eq!(simplify_polyline(&two, 1.0), two);
+    }
+
+    #[test]
+    fn simplifying_a_path_merges_straight_runs() {
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(0.0, 0.0))
+            .line_to(Point::new(5.0, 0.0))
+            .line_to(Point::new(10.0, 0.0))
+            .line_to(Point::new(10.0, 10.0))
+            .close();
+        let simplified = path.simplify(0.1);
+        let lines = simplified.segments.iter().filter(|segment| matches!(segment, PathSegment::Line { .. })).count();
+        assert_eq!(lines, 2);
+        assert!(matches!(simplified.segments.last(), Some(PathSegment::Close)));
+    }
+
+    fn polygon_on_circle(radius: f64, sides: usize, turn: f64) -> Vec<Point> {
+        (0..=sides)
+            .map(|i| {
+                let angle = turn * i as f64 / sides as f64;
+                Point::new(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn smoothing_a_ring_without_corners_leaves_no_seam() {
+        let points = polygon_on_circle(50.0, 24, std::f64::consts::TAU);
+        let mut path = VectorPath::new();
+        path.move_to(points[0]);
+        for point in &points[1..24] {
+            path.line_to(*point);
+        }
+        path.close();
+
+        let smoothed = path.smooth(0.5, 0.5);
+        assert!(smoothed.segments.iter().all(|s| !matches!(s, PathSegment::Line { .. })));
+        let curves = &smoothed.subpaths()[0].curves;
+        let (first, last) = (&curves[0], &curves[curves.len() - 1]);
+        assert_eq!(last.end, first.start);
+        assert!(turn_angle(&last.tangent_at(1.0), &first.tangent_at(0.0)) < 1e-6);
+        for pair in curves.windows(2) {
+            assert!(turn_angle(&pair[0].tangent_at(1.0), &pair[1].tangent_at(0.0)) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn smoothing_keeps_sharp_corners() {
+        // A half disc: the arc is smoothed, and the diameter meets it at two sharp corners
+        let points = polygon_on_circle(50.0, 12, std::f64::consts::PI);
+        let mut path = VectorPath::new();
+        path.move_to(points[0]);
+        for point in &points[1..] {
+            path.line_to(*point);
+        }
+        path.close();
+
+        let smoothed = path.smooth(0.5, 0.5);
+        let curves = &smoothed.subpaths()[0].curves;
+        for corner in [points[0], points[12]] {
+            let incoming = curves.iter().position(|c| c.end.distance_to(&corner) < 1e-9).unwrap();
+            let outgoing = &curves[(incoming + 1) % curves.len()];
+            assert_eq!(outgoing.start, corner);
+            assert!(turn_angle(&curves[incoming].tangent_at(1.0), &outgoing.tangent_at(0.0)) > 1.0);
+        }
+        // The diameter stays a straight line
+        assert!(smoothed.segments.contains(&PathSegment::Line { point: points[0] }));
+    }
+
+    #[test]
+    fn simplifying_refits_smooth_curve_runs() {
+        let arc = arc_to_curves(&Point::new(0.0, 0.0), 100.0, 0.0, std::f64::consts::FRAC_PI_2).remove(0);
+        let pieces: Vec<BezierCurve> = (0..4).map(|i| arc.subcurve(i as f64 / 4.0, (i + 1) as f64 / 4.0)).collect();
+        let mut path = VectorPath::new();
+        path.add_curves(&pieces, false);
+
+        let tolerance = 0.1;
+        let simplified = path.simplify(tolerance);
+        let curves = &simplified.subpaths()[0].curves;
+        assert!(curves.len() < pieces.len());
+        assert_eq!(curves[0].start, pieces[0].start);
+        assert_eq!(curves[curves.len() - 1].end, pieces[3].end);
+
+        // Everything the run drew is still within the tolerance of the refitted curves
+        for i in 0..=40 {
+            let point = arc.point_at(i as f64 / 40.0);
+            let distance = curves.iter().map(|c| c.nearest_point(&point).distance).fold(f64::INFINITY, f64::min);
+            assert!(distance <= tolerance, "{:?} is {} away", point, distance);
+        }
+    }
+}
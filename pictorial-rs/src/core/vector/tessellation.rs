@@ -0,0 +1,258 @@
+use super::engine::*;
+use super::offset::dashed;
+use super::types::*;
+use lyon::math::point;
+use lyon::path::iterator::PathIterator;
+use lyon::path::{Path, PathEvent};
+use lyon::tessellation::{
+    self as tess, BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator,
+    StrokeVertex, TessellationError, VertexBuffers,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// Triangles for the WebGL renderer. `vertices` holds x, y pairs in the path's own
+/// coordinates and `indices` three vertex indices per triangle.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mesh {
+    #[wasm_bindgen(skip)]
+    pub vertices: Vec<f32>,
+    #[wasm_bindgen(skip)]
+    pub indices: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl Mesh {
+    /// A copy of the vertices as a `Float32Array`.
+    #[wasm_bindgen(getter)]
+    pub fn vertices(&self) -> Vec<f32> {
+        self.vertices.clone()
+    }
+
+    /// A copy of the indices as a `Uint32Array`.
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+impl Mesh {
+    fn from_buffers(buffers: VertexBuffers<[f32; 2], u32>) -> Mesh {
+        Mesh {
+            vertices: buffers.vertices.into_iter().flatten().collect(),
+            indices: buffers.indices,
+        }
+    }
+}
+
+fn to_lyon(subpaths: &[Subpath]) -> Path {
+    let mut builder = Path::builder();
+    for subpath in subpaths {
+        let Some(first) = subpath.curves.first() else {
+            continue;
+        };
+        builder.begin(point(first.start.x as f32, first.start.y as f32));
+        for curve in &subpath.curves {
+            let to = point(curve.end.x as f32, curve.end.y as f32);
+            if curve.is_line() {
+                builder.line_to(to);
+            } else {
+                builder.cubic_bezier_to(
+                    point(curve.control1.x as f32, curve.control1.y as f32),
+                    point(curve.control2.x as f32, curve.control2.y as f32),
+                    to,
+                );
+            }
+        }
+        builder.end(subpath.closed);
+    }
+    builder.build()
+}
+
+impl VectorPath {
+    /// The path as a lyon path, with arcs as cubics.
+    pub fn to_lyon_path(&self) -> Path {
+        to_lyon(&self.subpaths())
+    }
+
+    /// Each subpath as a polyline within `tolerance` of the curves. Closed subpaths end
+    /// back at their first point.
+    pub fn flatten(&self, tolerance: f64) -> Vec<Vec<Point>> {
+        let mut polylines = Vec::new();
+        let mut current: Vec<Point> = Vec::new();
+        for event in self.to_lyon_path().iter().flattened(tolerance as f32) {
+            match event {
+                PathEvent::Begin { at } => current = vec![Point::new(at.x as f64, at.y as f64)],
+                PathEvent::Line { to, .. } => current.push(Point::new(to.x as f64, to.y as f64)),
+                PathEvent::End { first, close, .. } => {
+                    if close {
+                        current.push(Point::new(first.x as f64, first.y as f64));
+                    }
+                    polylines.push(std::mem::take(&mut current));
+                }
+                // Flattening leaves only lines
+                PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {}
+            }
+        }
+        polylines
+    }
+
+    /// Triangles covering the filled area under the path's fill rule.
+    pub fn tessellate_fill(&self, tolerance: f64) -> Result<Mesh, TessellationError> {
+        let fill_rule = match self.fill_rule {
+            FillRule::NonZero => tess::FillRule::NonZero,
+            FillRule::EvenOdd => tess::FillRule::EvenOdd,
+        };
+        let options = FillOptions::tolerance(tolerance as f32).with_fill_rule(fill_rule);
+
+        let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+        FillTessellator::new().tessellate_path(
+            &self.to_lyon_path(),
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| vertex.position().to_array()),
+        )?;
+        Ok(Mesh::from_buffers(buffers))
+    }
+
+    /// Triangles covering what `stroke` paints, with its caps, joins and dashes.
+    pub fn tessellate_stroke(&self, stroke: &Stroke, tolerance: f64) -> Result<Mesh, TessellationError> {
+        if stroke.width <= 0.0 {
+            return Ok(Mesh::default());
+        }
+
+        let mut subpaths = self.subpaths();
+        if let Some(pattern) = &stroke.dash_array {
+            subpaths = dashed(subpaths, pattern, stroke.line_cap != LineCap::Butt, tolerance);
+        }
+
+        let line_cap = match stroke.line_cap {
+            LineCap::Butt => tess::LineCap::Butt,
+            LineCap::Round => tess::LineCap::Round,
+            LineCap::Square => tess::LineCap::Square,
+        };
+        let line_join = match stroke.line_join {
+            LineJoin::Miter => tess::LineJoin::Miter,
+            LineJoin::Round => tess::LineJoin::Round,
+            LineJoin::Bevel => tess::LineJoin::Bevel,
+        };
+        let options = StrokeOptions::tolerance(tolerance as f32)
+            .with_line_width(stroke.width as f32)
+            .with_line_cap(line_cap)
+            .with_line_join(line_join)
+            .with_miter_limit(MITER_LIMIT as f32);
+
+        let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+        StrokeTessellator::new().tessellate_path(
+            &to_lyon(&subpaths),
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| vertex.position().to_array()),
+        )?;
+        Ok(Mesh::from_buffers(buffers))
+    }
+}
+
+impl VectorShape {
+    /// Fill triangles in the shape's local space (see `to_path`).
+    pub fn tessellate_fill(&self, tolerance: f64) -> Result<Mesh, TessellationError> {
+        self.to_path().tessellate_fill(tolerance)
+    }
+
+    pub fn tessellate_stroke(&self, stroke: &Stroke, tolerance: f64) -> Result<Mesh, TessellationError> {
+        self.to_path().tessellate_stroke(stroke, tolerance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(path: &mut VectorPath, at: f64, size: f64) {
+        path.move_to(Point::new(at, at))
+            .line_to(Point::new(at + size, at))
+            .line_to(Point::new(at + size, at + size))
+            .line_to(Point::new(at, at + size))
+            .close();
+    }
+
+    fn line(length: f64) -> VectorPath {
+        let mut path = VectorPath::new();
+        path.move_to(Point::new(0.0, 0.0)).line_to(Point::new(length, 0.0));
+        path
+    }
+
+    fn stroke(width: f64, dash_array: Option<Vec<f64>>) -> Stroke {
+        Stroke { color: "#000".to_string(), width, dash_array, line_cap: LineCap::Butt, line_join: LineJoin::Miter }
+    }
+
+    /// Total area of the mesh's triangles.
+    fn area(mesh: &Mesh) -> f64 {
+        let vertex = |index: u32| {
+            let index = index as usize * 2;
+            (mesh.vertices[index] as f64, mesh.vertices[index + 1] as f64)
+        };
+        mesh.indices
+            .chunks(3)
+            .map(|triangle| {
+                let (a, b, c) = (vertex(triangle[0]), vertex(triangle[1]), vertex(triangle[2]));
+                ((b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)).abs() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn fill_rule_decides_whether_the_hole_is_filled() {
+        // Both squares wind the same way, so only even-odd leaves the inner one empty
+        let mut ring = VectorPath::new();
+        square(&mut ring, 0.0, 10.0);
+        square(&mut ring, 2.0, 6.0);
+
+        ring.fill_rule = FillRule::NonZero;
+        let solid = ring.tessellate_fill(0.01).unwrap();
+        ring.fill_rule = FillRule::EvenOdd;
+        let holed = ring.tessellate_fill(0.01).unwrap();
+
+        // The hole's corners split both meshes, but only the solid one covers the middle
+        assert_ne!(holed.triangle_count(), solid.triangle_count());
+        assert!((area(&solid) - 100.0).abs() < 1e-3);
+        assert!((area(&holed) - 64.0).abs() < 1e-3);
+        assert_eq!(holed.indices.len(), holed.triangle_count() * 3);
+        assert!(holed.indices.iter().all(|index| (*index as usize) < holed.vertex_count()));
+    }
+
+    #[test]
+    fn zero_width_strokes_are_empty() {
+        assert_eq!(line(10.0).tessellate_stroke(&stroke(0.0, None), 0.01).unwrap(), Mesh::default());
+        assert_eq!(line(10.0).tessellate_stroke(&stroke(-1.0, None), 0.01).unwrap(), Mesh::default());
+    }
+
+    #[test]
+    fn strokes_cover_their_width_and_dashes() {
+        let solid = line(10.0).tessellate_stroke(&stroke(2.0, None), 0.01).unwrap();
+        assert!((area(&solid) - 20.0).abs() < 1e-3);
+
+        // Dashes of 3 with gaps of 2 paint 0..3 and 5..8
+        let dashed = line(10.0).tessellate_stroke(&stroke(2.0, Some(vec![3.0, 2.0])), 0.01).unwrap();
+        assert!((area(&dashed) - 12.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn flattening_closes_closed_subpaths() {
+        let mut path = VectorPath::new();
+        square(&mut path, 0.0, 10.0);
+        let polylines = path.flatten(0.01);
+        assert_eq!(polylines.len(), 1);
+        assert_eq!(polylines[0].first(), polylines[0].last());
+        assert_eq!(line(10.0).flatten(0.01), vec![vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0)]]);
+    }
+}
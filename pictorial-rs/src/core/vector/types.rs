@@ -1,3 +1,4 @@
+use crate::math::{Decomposition, Matrix3};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -189,39 +190,71 @@ impl Transform {
         }
     }
 
-    pub fn compose(&self, other: &Transform) -> Transform {
-        // Simplified composition - would need proper matrix math for full accuracy
+    /// Skew angles in radians, as in CSS `skew(ax, ay)`.
+    pub fn skew(skew_x: f64, skew_y: f64) -> Transform {
         Transform {
-            translate_x: self.translate_x + other.translate_x,
-            translate_y: self.translate_y + other.translate_y,
-            scale_x: self.scale_x * other.scale_x,
-            scale_y: self.scale_y * other.scale_y,
-            rotation: self.rotation + other.rotation,
-            skew_x: self.skew_x + other.skew_x,
-            skew_y: self.skew_y + other.skew_y,
+            skew_x,
+            skew_y,
+            ..Transform::identity()
         }
     }
 
+    /// Returns the transform that applies `other` first and then `self`, so
+    /// `parent.compose(&child)` maps child-local coordinates into the parent's space.
+    pub fn compose(&self, other: &Transform) -> Transform {
+        Transform::from_matrix(&(self.to_matrix() * other.to_matrix()))
+    }
+
+    pub fn inverse(&self) -> Option<Transform> {
+        self.to_matrix().invert().map(|m| Transform::from_matrix(&m))
+    }
+
     pub fn transform_point(&self, point: &Point) -> Point {
-        let cos_r = self.rotation.cos();
-        let sin_r = self.rotation.sin();
-        
-        // Apply scale
-        let scaled_x = point.x * self.scale_x;
-        let scaled_y = point.y * self.scale_y;
-        
-        // Apply rotation
-        let rotated_x = scaled_x * cos_r - scaled_y * sin_r;
-        let rotated_y = scaled_x * sin_r + scaled_y * cos_r;
-        
-        // Apply translation
-        Point {
-            x: rotated_x + self.translate_x,
-            y: rotated_y + self.translate_y,
+        let (x, y) = self.to_matrix().transform_coords(point.x, point.y);
+        Point { x, y }
+    }
+}
+
+impl Transform {
+    /// Scale, then skew, then rotate, then translate.
+    pub fn to_matrix(&self) -> Matrix3 {
+        Matrix3::compose(&Decomposition {
+            translate_x: self.translate_x,
+            translate_y: self.translate_y,
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            rotation: self.rotation,
+            skew_x: self.skew_x,
+            skew_y: self.skew_y,
+        })
+    }
+
+    pub fn from_matrix(matrix: &Matrix3) -> Transform {
+        let parts = matrix.decompose();
+        Transform {
+            translate_x: parts.translate_x,
+            translate_y: parts.translate_y,
+            scale_x: parts.scale_x,
+            scale_y: parts.scale_y,
+            rotation: parts.rotation,
+            skew_x: parts.skew_x,
+            skew_y: parts.skew_y,
         }
     }
 }
 
+impl From<&Transform> for Matrix3 {
+    fn from(transform: &Transform) -> Matrix3 {
+        transform.to_matrix()
+    }
+}
+
+impl From<Matrix3> for Transform {
+    fn from(matrix: Matrix3) -> Transform {
+        Transform::from_matrix(&matrix)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BezierCurve {
     pub start: Point,
@@ -260,6 +293,8 @@ pub struct VectorPath {
     pub stroke_color: Option<String>,
     pub stroke_width: Option<f64>,
     pub opacity: Option<f64>,
+    #[serde(default)]
+    pub fill_rule: FillRule,
 }
 
 impl VectorPath {
@@ -272,6 +307,25 @@ impl VectorPath {
             stroke_color: None,
             stroke_width: None,
             opacity: None,
+            fill_rule: FillRule::default(),
+        }
+    }
+}
+
+/// Which areas of a self-overlapping path are filled, as in canvas `fill()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    pub fn is_inside(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
         }
     }
 }
@@ -371,6 +425,9 @@ pub enum VectorElement {
         z_index: i32,
         path: VectorPath,
         parent: Option<String>,
+        /// Rotation and scale pivot in the element's local coordinates, if the user moved it.
+        #[serde(default)]
+        pivot: Option<Point>,
     },
     #[serde(rename = "shape")]
     Shape {
@@ -383,6 +440,9 @@ pub enum VectorElement {
         z_index: i32,
         shape: VectorShape,
         parent: Option<String>,
+        /// Rotation and scale pivot in the element's local coordinates, if the user moved it.
+        #[serde(default)]
+        pivot: Option<Point>,
     },
     #[serde(rename = "text")]
     Text {
@@ -395,6 +455,9 @@ pub enum VectorElement {
         z_index: i32,
         text: VectorText,
         parent: Option<String>,
+        /// Rotation and scale pivot in the element's local coordinates, if the user moved it.
+        #[serde(default)]
+        pivot: Option<Point>,
     },
     #[serde(rename = "group")]
     Group {
@@ -407,6 +470,9 @@ pub enum VectorElement {
         z_index: i32,
         children: Vec<String>,
         parent: Option<String>,
+        /// Rotation and scale pivot in the element's local coordinates, if the user moved it.
+        #[serde(default)]
+        pivot: Option<Point>,
     },
 }
 
@@ -438,6 +504,24 @@ impl VectorElement {
         }
     }
 
+    pub fn transform_mut(&mut self) -> &mut Transform {
+        match self {
+            VectorElement::Path { transform, .. } => transform,
+            VectorElement::Shape { transform, .. } => transform,
+            VectorElement::Text { transform, .. } => transform,
+            VectorElement::Group { transform, .. } => transform,
+        }
+    }
+
+    pub fn bounding_box_mut(&mut self) -> &mut BoundingBox {
+        match self {
+            VectorElement::Path { bounding_box, .. } => bounding_box,
+            VectorElement::Shape { bounding_box, .. } => bounding_box,
+            VectorElement::Text { bounding_box, .. } => bounding_box,
+            VectorElement::Group { bounding_box, .. } => bounding_box,
+        }
+    }
+
     pub fn is_visible(&self) -> bool {
         match self {
             VectorElement::Path { visible, .. } => *visible,
@@ -447,6 +531,33 @@ impl VectorElement {
         }
     }
 
+    pub fn parent(&self) -> Option<&str> {
+        match self {
+            VectorElement::Path { parent, .. } => parent.as_deref(),
+            VectorElement::Shape { parent, .. } => parent.as_deref(),
+            VectorElement::Text { parent, .. } => parent.as_deref(),
+            VectorElement::Group { parent, .. } => parent.as_deref(),
+        }
+    }
+
+    pub fn pivot(&self) -> Option<&Point> {
+        match self {
+            VectorElement::Path { pivot, .. } => pivot.as_ref(),
+            VectorElement::Shape { pivot, .. } => pivot.as_ref(),
+            VectorElement::Text { pivot, .. } => pivot.as_ref(),
+            VectorElement::Group { pivot, .. } => pivot.as_ref(),
+        }
+    }
+
+    pub fn pivot_mut(&mut self) -> &mut Option<Point> {
+        match self {
+            VectorElement::Path { pivot, .. } => pivot,
+            VectorElement::Shape { pivot, .. } => pivot,
+            VectorElement::Text { pivot, .. } => pivot,
+            VectorElement::Group { pivot, .. } => pivot,
+        }
+    }
+
     pub fn is_locked(&self) -> bool {
         match self {
             VectorElement::Path { locked, .. } => *locked,
@@ -455,6 +566,24 @@ impl VectorElement {
             VectorElement::Group { locked, .. } => *locked,
         }
     }
+
+    pub fn z_index(&self) -> i32 {
+        match self {
+            VectorElement::Path { z_index, .. } => *z_index,
+            VectorElement::Shape { z_index, .. } => *z_index,
+            VectorElement::Text { z_index, .. } => *z_index,
+            VectorElement::Group { z_index, .. } => *z_index,
+        }
+    }
+
+    pub fn style(&self) -> &Style {
+        match self {
+            VectorElement::Path { style, .. } => style,
+            VectorElement::Shape { style, .. } => style,
+            VectorElement::Text { style, .. } => style,
+            VectorElement::Group { style, .. } => style,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -521,6 +650,8 @@ pub struct VectorText {
     pub path: Option<VectorPath>,
 }
 
+/// `bounds` is the selection box in its own frame and `transform` maps that frame onto
+/// the canvas. Multi-selections use canvas-aligned bounds and the identity transform.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Selection {
     pub elements: Vec<String>,
@@ -528,6 +659,83 @@ pub struct Selection {
     pub transform: Transform,
 }
 
+impl Selection {
+    /// The selection box for `elements`. A single rotated or scaled element gets its
+    /// oriented box, recovered from its canvas `bounding_box` by undoing the rotation,
+    /// so its handles sit on its corners. Several elements, or one that is skewed or
+    /// turned by about 45° (where the canvas box no longer pins down the sides), get
+    /// their canvas-aligned bounds with the identity transform.
+    pub fn from_elements(elements: &[VectorElement]) -> Option<Selection> {
+        let ids = elements.iter().map(|el| el.id().to_string()).collect();
+        if let [element] = elements {
+            if let Some(bounds) = Self::oriented_bounds(element.bounding_box(), element.transform()) {
+                return Some(Selection { elements: ids, bounds, transform: element.transform().clone() });
+            }
+        }
+
+        let mut boxes = elements.iter().map(|el| el.bounding_box().clone());
+        let first = boxes.next()?;
+        Some(Selection {
+            elements: ids,
+            bounds: boxes.fold(first, |acc, b| acc.union(&b)),
+            transform: Transform::identity(),
+        })
+    }
+
+    /// The local box that `transform` maps onto a rectangle whose canvas-aligned
+    /// bounds are `canvas`. A `w` x `h` box rotated by θ covers
+    /// `w·|cos θ| + h·|sin θ|` by `w·|sin θ| + h·|cos θ|`, which is solved for the
+    /// rotated sides before dividing out the scale.
+    fn oriented_bounds(canvas: &BoundingBox, transform: &Transform) -> Option<BoundingBox> {
+        if transform.skew_x != 0.0 || transform.skew_y != 0.0 {
+            return None;
+        }
+
+        let (cos, sin) = (transform.rotation.cos().abs(), transform.rotation.sin().abs());
+        let det = cos * cos - sin * sin;
+        if det.abs() < 1e-6 {
+            return None;
+        }
+        let width = (canvas.width * cos - canvas.height * sin) / det;
+        let height = (canvas.height * cos - canvas.width * sin) / det;
+        if width < -1e-9 || height < -1e-9 {
+            return None;
+        }
+
+        let width = width.max(0.0) / transform.scale_x.abs();
+        let height = height.max(0.0) / transform.scale_y.abs();
+        let center = transform.inverse()?.transform_point(&canvas.center());
+        Some(BoundingBox::new(center.x - width / 2.0, center.y - height / 2.0, width, height))
+    }
+}
+
+/// A drawing as it is saved: its elements and the ruler guides drawn over them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub elements: Vec<VectorElement>,
+    #[serde(default)]
+    pub ruler_guides: Vec<RulerGuide>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlignmentType {
+    Horizontal,
+    Vertical,
+}
+
+/// A guide the user dragged out of a ruler. Unlike the temporary smart guides these are
+/// part of the `Document`. Guides with an `artboard_id` only apply to that artboard's
+/// children; the rest apply everywhere.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RulerGuide {
+    pub id: String,
+    pub guide_type: AlignmentType,
+    pub position: f64,
+    pub locked: bool,
+    pub artboard_id: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GridSettings {
     pub enabled: bool,
@@ -599,4 +807,47 @@ pub struct CollaborationCursor {
     pub color: String,
     pub position: Point,
     pub tool: String,
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn compose_places_a_child_inside_a_rotated_parent() {
+        let parent = Transform { translate_x: 100.0, rotation: FRAC_PI_2, ..Transform::identity() };
+        let child = Transform::translate(10.0, 0.0);
+
+        // | 0 -1 100 |   | 1 0 10 |   | 0 -1 100 |
+        // | 1  0   0 | * | 0 1  0 | = | 1  0  10 |
+        let composed = parent.compose(&child).to_matrix();
+        let expected = [0.0, 1.0, -1.0, 0.0, 100.0, 10.0];
+        let actual = [composed.a, composed.b, composed.c, composed.d, composed.e, composed.f];
+        for (x, y) in actual.iter().zip(expected) {
+            assert!((x - y).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+
+        // The child's origin lands 10 units along the parent's rotated x axis
+        let origin = parent.compose(&child).transform_point(&Point::new(0.0, 0.0));
+        assert!((origin.x - 100.0).abs() < 1e-9 && (origin.y - 10.0).abs() < 1e-9);
+        let corner = parent.compose(&child).transform_point(&Point::new(5.0, 0.0));
+        assert!((corner.x - 100.0).abs() < 1e-9 && (corner.y - 15.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn documents_saved_without_ruler_guides_still_load() {
+        let document: Document = serde_json::from_str(r#"{"elements": []}"#).unwrap();
+        assert_eq!(document, Document::default());
+
+        let guide = RulerGuide {
+            id: "g".to_string(),
+            guide_type: AlignmentType::Vertical,
+            position: 12.0,
+            locked: true,
+            artboard_id: None,
+        };
+        let saved = Document { elements: Vec::new(), ruler_guides: vec![guide] };
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(serde_json::from_str::<Document>(&json).unwrap(), saved);
+    }
+}
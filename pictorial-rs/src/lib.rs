@@ -17,13 +17,21 @@ pub mod core {
 }
 
 pub mod components;
+pub mod math;
 pub mod stores;
 pub mod utils;
 pub mod workers;
 
 // Re-export commonly used types for JavaScript
 pub use core::vector::*;
-pub use core::transform::*;
+// `core::vector` has its own `engine` module, so the transform types are listed
+// instead of glob-imported.
+pub use core::transform::{
+    AlignTarget, Alignment, AlignmentGuide, Constraint, ConstraintType, Distribution, FrameGrids, GapMarker,
+    GridAlignment, GridKind, GridTrack, HandleType, LayoutGrid, NudgeDirection, NudgeMode, NudgeResult, PixelGrid,
+    ReferencePoint, SelectionHandle, SnapResult, SpacingAxis, SpacingGuide, TransformAction, TransformActionType,
+    TransformEngine, TransformError, TransformScope, TransformUpdate, TransformValues,
+};
 
 // Initialize the console error panic hook for better debugging
 #[wasm_bindgen(start)]
@@ -53,10 +61,215 @@ impl PictorialEngine {
         }
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn transform_engine(&self) -> core::transform::TransformEngine {
-        // Note: This clone might be expensive, consider using references or Arc<Mutex<>> for shared state
-        self.transform_engine.clone()
+    // Everything below is forwarded to the shared engine so JS callers get
+    // exactly the behavior Rust callers of `TransformEngine` see
+
+    // SELECTION HANDLES
+    pub fn generate_selection_handles(&self, selection: JsValue) -> Result<JsValue, JsValue> {
+        self.transform_engine.generate_selection_handles_js(selection)
+    }
+
+    pub fn selection_for_elements(elements: JsValue) -> Result<JsValue, JsValue> {
+        core::transform::TransformEngine::selection_for_elements_js(elements)
+    }
+
+    pub fn selection_handles(&self, elements: JsValue) -> Result<JsValue, JsValue> {
+        self.transform_engine.selection_handles_js(elements)
+    }
+
+    pub fn get_handle_at_point(&self, handles: JsValue, point: &Point) -> Result<JsValue, JsValue> {
+        self.transform_engine.get_handle_at_point_js(handles, point)
+    }
+
+    // TRANSFORM SESSIONS
+    pub fn start_transform(
+        &mut self,
+        element_ids: JsValue,
+        handle_type: HandleType,
+        start_point: Point,
+        document: JsValue,
+    ) -> Result<(), JsValue> {
+        self.transform_engine.start_transform_js(element_ids, handle_type, start_point, document)
+    }
+
+    /// Ctrl-drag on an edge handle.
+    pub fn start_skew(
+        &mut self,
+        element_ids: JsValue,
+        handle_type: HandleType,
+        start_point: Point,
+        document: JsValue,
+    ) -> Result<(), JsValue> {
+        self.transform_engine.start_skew_js(element_ids, handle_type, start_point, document)
+    }
+
+    pub fn update_transform(&mut self, current_point: Point) -> Result<JsValue, JsValue> {
+        self.transform_engine.update_transform_js(current_point)
+    }
+
+    pub fn finish_transform(&mut self) -> Result<JsValue, JsValue> {
+        self.transform_engine.finish_transform_js()
+    }
+
+    pub fn cancel_transform(&mut self) -> Result<JsValue, JsValue> {
+        self.transform_engine.cancel_transform_js()
+    }
+
+    pub fn is_transforming(&self) -> bool {
+        self.transform_engine.is_transforming()
+    }
+
+    // NUDGING
+    pub fn nudge(
+        &mut self,
+        element_ids: JsValue,
+        direction: JsValue,
+        big: bool,
+        elements: JsValue,
+        mode: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        self.transform_engine.nudge_js(element_ids, direction, big, elements, mode)
+    }
+
+    pub fn end_nudge(&mut self) {
+        self.transform_engine.end_nudge();
+    }
+
+    // ALIGNMENT AND TRANSFORM VALUES
+    pub fn align_elements(&self, elements: JsValue, alignment: &str, target: JsValue) -> Result<JsValue, JsValue> {
+        self.transform_engine.align_elements_js(elements, alignment, target)
+    }
+
+    pub fn transform_values(elements: JsValue, reference: JsValue) -> Result<JsValue, JsValue> {
+        core::transform::TransformEngine::transform_values_js(elements, reference)
+    }
+
+    pub fn set_transform_values(
+        &self,
+        elements: JsValue,
+        values: JsValue,
+        reference: JsValue,
+        scope: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        self.transform_engine.set_transform_values_js(elements, values, reference, scope)
+    }
+
+    pub fn distribute_elements(&self, elements: JsValue, axis: JsValue, distribution: JsValue) -> Result<JsValue, JsValue> {
+        self.transform_engine.distribute_elements_js(elements, axis, distribution)
+    }
+
+    pub fn tidy_up(&self, elements: JsValue, gap: Option<f64>) -> Result<JsValue, JsValue> {
+        self.transform_engine.tidy_up_js(elements, gap)
+    }
+
+    // FLIPPING
+    pub fn flip_horizontal(&self, elements: JsValue, keep_text_readable: bool) -> Result<JsValue, JsValue> {
+        self.transform_engine.flip_horizontal_js(elements, keep_text_readable)
+    }
+
+    pub fn flip_vertical(&self, elements: JsValue, keep_text_readable: bool) -> Result<JsValue, JsValue> {
+        self.transform_engine.flip_vertical_js(elements, keep_text_readable)
+    }
+
+    pub fn reflect_across(
+        &self,
+        elements: JsValue,
+        axis_point: &Point,
+        angle: f64,
+        keep_text_readable: bool,
+    ) -> Result<JsValue, JsValue> {
+        self.transform_engine.reflect_across_js(elements, axis_point, angle, keep_text_readable)
+    }
+
+    // PIVOTS
+    pub fn set_pivot(&self, elements: JsValue, pivot: JsValue) -> Result<JsValue, JsValue> {
+        self.transform_engine.set_pivot_js(elements, pivot)
+    }
+
+    pub fn snap_pivot(&self, elements: JsValue, point: &Point) -> Result<Point, JsValue> {
+        self.transform_engine.snap_pivot_js(elements, point)
+    }
+
+    pub fn selection_pivot(elements: JsValue) -> Result<JsValue, JsValue> {
+        core::transform::TransformEngine::selection_pivot_js(elements)
+    }
+
+    // CONSTRAINTS
+    pub fn set_constraint(&mut self, constraint_type: JsValue, enabled: bool, params: JsValue) -> Result<(), JsValue> {
+        self.transform_engine.set_constraint_js(constraint_type, enabled, params)
+    }
+
+    pub fn constraints(&self) -> Result<JsValue, JsValue> {
+        self.transform_engine.constraints_js()
+    }
+
+    // GRID AND SNAPPING
+    pub fn set_grid(&mut self, enabled: bool, size: f64, color: String, opacity: f64, snap: bool) {
+        self.transform_engine.set_grid(enabled, size, color, opacity, snap);
+    }
+
+    pub fn snap_to_grid(&self, point: &Point) -> Point {
+        self.transform_engine.snap_to_grid(point)
+    }
+
+    pub fn set_frame_grids(&mut self, frame: JsValue) -> Result<(), JsValue> {
+        self.transform_engine.set_frame_grids_js(frame)
+    }
+
+    pub fn remove_frame_grids(&mut self, frame_id: &str) {
+        self.transform_engine.remove_frame_grids(frame_id);
+    }
+
+    pub fn set_snap_threshold(&mut self, threshold: f64) {
+        self.transform_engine.set_snap_threshold(threshold);
+    }
+
+    pub fn alignment_guides(&self) -> Result<JsValue, JsValue> {
+        self.transform_engine.alignment_guides_js()
+    }
+
+    // RULER GUIDES
+    pub fn add_ruler_guide(
+        document: JsValue,
+        guide_type: JsValue,
+        position: f64,
+        artboard_id: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        core::transform::TransformEngine::add_ruler_guide_js(document, guide_type, position, artboard_id)
+    }
+
+    pub fn move_ruler_guide(document: JsValue, id: &str, position: f64) -> Result<JsValue, JsValue> {
+        core::transform::TransformEngine::move_ruler_guide_js(document, id, position)
+    }
+
+    pub fn delete_ruler_guide(document: JsValue, id: &str) -> Result<JsValue, JsValue> {
+        core::transform::TransformEngine::delete_ruler_guide_js(document, id)
+    }
+
+    pub fn set_ruler_guide_locked(document: JsValue, id: &str, locked: bool) -> Result<JsValue, JsValue> {
+        core::transform::TransformEngine::set_ruler_guide_locked_js(document, id, locked)
+    }
+
+    // PIXEL SNAPPING
+    pub fn set_pixel_snapping(&mut self, enabled: bool, export_scale: f64) {
+        self.transform_engine.set_pixel_snapping(enabled, export_scale);
+    }
+
+    pub fn set_pixel_grid(&mut self, pixel_grid: JsValue) -> Result<(), JsValue> {
+        self.transform_engine.set_pixel_grid_js(pixel_grid)
+    }
+
+    pub fn snap_to_pixels(&self, elements: JsValue) -> Result<JsValue, JsValue> {
+        self.transform_engine.snap_to_pixels_js(elements)
+    }
+
+    // VIEWPORT
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.transform_engine.set_viewport(viewport);
+    }
+
+    pub fn get_viewport(&self) -> Viewport {
+        self.transform_engine.get_viewport()
     }
 }
 
@@ -0,0 +1,275 @@
+use crate::core::vector::Point;
+use serde::{Deserialize, Serialize};
+use std::ops::Mul;
+
+const EPSILON: f64 = 1e-12;
+
+/// A 2D affine transform stored as the top two rows of a 3x3 matrix:
+///
+/// ```text
+/// | a  c  e |
+/// | b  d  f |
+/// | 0  0  1 |
+/// ```
+///
+/// This is the same layout used by canvas `setTransform` and SVG `matrix()`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Matrix3 {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+/// A matrix split into translation, rotation, skew and scale, applied to a
+/// point in the order scale -> skew -> rotate -> translate.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Decomposition {
+    pub translate_x: f64,
+    pub translate_y: f64,
+    pub scale_x: f64,
+    pub scale_y: f64,
+    pub rotation: f64,
+    pub skew_x: f64,
+    pub skew_y: f64,
+}
+
+impl Matrix3 {
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Matrix3 {
+        Matrix3 { a, b, c, d, e, f }
+    }
+
+    pub fn identity() -> Matrix3 {
+        Matrix3::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn translation(tx: f64, ty: f64) -> Matrix3 {
+        Matrix3::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    pub fn scale(sx: f64, sy: f64) -> Matrix3 {
+        Matrix3::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    pub fn rotation(angle: f64) -> Matrix3 {
+        let (sin, cos) = angle.sin_cos();
+        Matrix3::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Skew by the given angles (radians), matching CSS `skew(ax, ay)`.
+    pub fn skew(skew_x: f64, skew_y: f64) -> Matrix3 {
+        Matrix3::new(1.0, skew_y.tan(), skew_x.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// Mirror across the line through the origin at `angle` (radians from the x axis).
+    pub fn reflection(angle: f64) -> Matrix3 {
+        let (sin, cos) = (2.0 * angle).sin_cos();
+        Matrix3::new(cos, sin, sin, -cos, 0.0, 0.0)
+    }
+
+    /// Returns `self * other`: the transform that applies `other` first and then `self`.
+    pub fn multiply(&self, other: &Matrix3) -> Matrix3 {
+        Matrix3 {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    pub fn is_invertible(&self) -> bool {
+        self.determinant().abs() > EPSILON
+    }
+
+    /// Returns `None` when the matrix is singular (e.g. scaled to zero on an axis).
+    pub fn invert(&self) -> Option<Matrix3> {
+        let det = self.determinant();
+        if det.abs() <= EPSILON {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        Some(Matrix3 {
+            a: self.d * inv_det,
+            b: -self.b * inv_det,
+            c: -self.c * inv_det,
+            d: self.a * inv_det,
+            e: (self.c * self.f - self.d * self.e) * inv_det,
+            f: (self.b * self.e - self.a * self.f) * inv_det,
+        })
+    }
+
+    pub fn transform_coords(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Like `transform_coords` but ignores translation, for directions and offsets.
+    pub fn transform_vector(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.c * y, self.b * x + self.d * y)
+    }
+
+    pub fn transform_point(&self, point: &Point) -> Point {
+        let (x, y) = self.transform_coords(point.x, point.y);
+        Point::new(x, y)
+    }
+
+    /// The translation part of the matrix. This is the `translation()` getter, named
+    /// apart from the `translation(tx, ty)` constructor since Rust can't overload them.
+    pub fn translation_vector(&self) -> Point {
+        Point::new(self.e, self.f)
+    }
+
+    pub fn set_translation(&mut self, translation: &Point) {
+        self.e = translation.x;
+        self.f = translation.y;
+    }
+
+    /// Builds the matrix `T * R * K * S` from its components.
+    pub fn compose(parts: &Decomposition) -> Matrix3 {
+        Matrix3::translation(parts.translate_x, parts.translate_y)
+            * Matrix3::rotation(parts.rotation)
+            * Matrix3::skew(parts.skew_x, parts.skew_y)
+            * Matrix3::scale(parts.scale_x, parts.scale_y)
+    }
+
+    /// Splits the matrix into translation, rotation, horizontal skew and scale.
+    ///
+    /// A general affine matrix has six degrees of freedom, so the result always
+    /// has `skew_y == 0.0`. Reflections show up as a negative `scale_y`.
+    pub fn decompose(&self) -> Decomposition {
+        let scale_x = (self.a * self.a + self.b * self.b).sqrt();
+        let rotation = if scale_x > EPSILON { self.b.atan2(self.a) } else { 0.0 };
+        let (sin, cos) = rotation.sin_cos();
+
+        // Undo the rotation on the second column: R^T * (c, d) = (tan(skew) * sy, sy)
+        let scale_y = cos * self.d - sin * self.c;
+        let sheared = cos * self.c + sin * self.d;
+        let skew_x = if scale_y.abs() > EPSILON {
+            (sheared / scale_y).atan()
+        } else {
+            0.0
+        };
+
+        Decomposition {
+            translate_x: self.e,
+            translate_y: self.f,
+            scale_x,
+            scale_y,
+            rotation,
+            skew_x,
+            skew_y: 0.0,
+        }
+    }
+}
+
+impl Default for Matrix3 {
+    fn default() -> Self {
+        Matrix3::identity()
+    }
+}
+
+impl Mul for Matrix3 {
+    type Output = Matrix3;
+
+    fn mul(self, rhs: Matrix3) -> Matrix3 {
+        self.multiply(&rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_matrix_eq(actual: &Matrix3, expected: &Matrix3) {
+        let pairs = [
+            (actual.a, expected.a),
+            (actual.b, expected.b),
+            (actual.c, expected.c),
+            (actual.d, expected.d),
+            (actual.e, expected.e),
+            (actual.f, expected.f),
+        ];
+        for (x, y) in pairs {
+            assert!((x - y).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn multiply_applies_the_right_operand_first() {
+        let m = Matrix3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0);
+        let n = Matrix3::new(-1.0, 0.5, 2.0, 1.0, 3.0, -2.0);
+        assert_matrix_eq(&m.multiply(&n), &Matrix3::new(0.5, 0.0, 5.0, 8.0, 2.0, 4.0));
+        assert_matrix_eq(&(m * Matrix3::identity()), &m);
+
+        // Rotate (1, 0) onto (0, 1), then move it
+        let rotate_then_move = Matrix3::translation(10.0, 0.0) * Matrix3::rotation(FRAC_PI_2);
+        let (x, y) = rotate_then_move.transform_coords(1.0, 0.0);
+        assert!((x - 10.0).abs() < 1e-9 && (y - 1.0).abs() < 1e-9);
+
+        let move_then_rotate = Matrix3::rotation(FRAC_PI_2) * Matrix3::translation(10.0, 0.0);
+        let (x, y) = move_then_rotate.transform_coords(1.0, 0.0);
+        assert!(x.abs() < 1e-9 && (y - 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn invert_undoes_the_matrix() {
+        let m = Matrix3::translation(7.0, -3.0) * Matrix3::rotation(0.4) * Matrix3::scale(2.0, 0.5);
+        let inverse = m.invert().unwrap();
+        assert_matrix_eq(&(m * inverse), &Matrix3::identity());
+        assert_matrix_eq(&(inverse * m), &Matrix3::identity());
+
+        let back = inverse.transform_point(&m.transform_point(&Point::new(3.0, 4.0)));
+        assert!((back.x - 3.0).abs() < 1e-9 && (back.y - 4.0).abs() < 1e-9);
+        assert_eq!(m.translation_vector(), Point::new(7.0, -3.0));
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        assert_eq!(Matrix3::scale(0.0, 1.0).invert(), None);
+        assert_eq!(Matrix3::new(1.0, 2.0, 2.0, 4.0, 5.0, 6.0).invert(), None);
+        assert!(!Matrix3::scale(3.0, 0.0).is_invertible());
+    }
+
+    #[test]
+    fn decompose_round_trips_rotation_skew_and_scale() {
+        let parts = Decomposition {
+            translate_x: 12.0,
+            translate_y: -4.0,
+            scale_x: 2.0,
+            scale_y: -0.5,
+            rotation: 0.6,
+            skew_x: 0.3,
+            skew_y: 0.0,
+        };
+        let matrix = Matrix3::compose(&parts);
+        let decomposed = matrix.decompose();
+        let pairs = [
+            (decomposed.translate_x, parts.translate_x),
+            (decomposed.translate_y, parts.translate_y),
+            (decomposed.scale_x, parts.scale_x),
+            (decomposed.scale_y, parts.scale_y),
+            (decomposed.rotation, parts.rotation),
+            (decomposed.skew_x, parts.skew_x),
+        ];
+        for (x, y) in pairs {
+            assert!((x - y).abs() < 1e-9, "{:?} != {:?}", decomposed, parts);
+        }
+        assert_eq!(decomposed.skew_y, 0.0);
+
+        // A vertical skew is folded into the other parts but the matrix survives
+        let skewed_both = Matrix3::compose(&Decomposition { skew_y: -0.2, ..parts });
+        assert_matrix_eq(&Matrix3::compose(&skewed_both.decompose()), &skewed_both);
+    }
+}
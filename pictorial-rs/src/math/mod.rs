@@ -0,0 +1,3 @@
+pub mod matrix;
+
+pub use matrix::*;